@@ -1,4 +1,4 @@
-use imgoodies::{memory::MemoryEditor, Framework};
+use imgoodies::{gfx::GfxContext, memory::MemoryEditor, Framework};
 use imgui::Ui;
 use std::mem::MaybeUninit;
 
@@ -20,9 +20,10 @@ fn main() {
         std::ptr::copy(b"Hello World!", state.data.as_mut_ptr().cast(), 1);
     }
 
-    Framework::new("Memory Editor demo", state).run(draw);
+    Framework::new("Memory Editor demo", state).run_with_gfx(draw);
 }
 
-fn draw(ui: &mut Ui, state: &mut State) {
+fn draw(ui: &mut Ui, state: &mut State, gfx: &mut GfxContext) {
     state.editor.draw_window(ui, state.data.as_mut_slice());
+    state.editor.upload_textures(gfx);
 }
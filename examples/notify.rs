@@ -1,27 +1,60 @@
 use imgoodies::{notify::*, Framework};
 use imgui::{Condition, TreeNodeFlags, Ui};
+use std::{thread, time::Duration};
+
+const ACTION_UNDO: usize = 0;
 
 struct State {
     notifications: Notifications,
     title: String,
     content: String,
     duration: i32,
-    typ: ToastType,
+    /// Index in `ToastType::ALL`
+    typ: usize,
+    deploy: ToastType,
+    light_theme: bool,
     enable_title: bool,
     enable_content: bool,
+    closable: bool,
+    job: Option<(ToastId, f32)>,
+    show_history: bool,
+    rename: Option<DialogHandle>,
+}
+
+/// A theme with a custom type for deploys.
+fn theme(light: bool) -> (ToastTheme, ToastType) {
+    const DEPLOY_COLOR: [f32; 4] = [0.6, 0.35, 0.9, 1.0];
+
+    let (mut theme, deploy) = if light {
+        (ToastTheme::light(), ToastStyle::light(DEPLOY_COLOR))
+    } else {
+        (ToastTheme::dark(), ToastStyle::dark(DEPLOY_COLOR))
+    };
+    let deploy = theme.add_type("Deploy", deploy, Some(ToastIcon::Glyph('>')));
+    (theme, deploy)
 }
 
 fn main() {
+    let (theme, deploy) = theme(false);
+    let mut notifications = Notifications::default();
+    notifications.config().theme = theme;
+
     Framework::new(
         "Notification demo",
         State {
-            notifications: Notifications::default(),
+            notifications,
             title: "A wonderful quote!".to_string(),
             content: String::new(),
             duration: 5000,
-            typ: ToastType::Success,
+            typ: 1,
+            deploy,
+            light_theme: false,
             enable_title: true,
             enable_content: true,
+            closable: false,
+            job: None,
+            show_history: false,
+            rename: None,
         },
     )
     .run(draw);
@@ -54,6 +87,125 @@ fn draw(ui: &mut Ui, state: &mut State) {
 				}
             }
 
+            if ui.collapsing_header("Interactive", TreeNodeFlags::DEFAULT_OPEN) {
+                if ui.button("Delete file") {
+                    state.notifications.push(
+                        Toast::new(ToastType::Info, 5000)
+                            .content("File deleted")
+                            .action("Undo", ACTION_UNDO),
+                    );
+                }
+                ui.same_line();
+                if ui.button("Start job") && state.job.is_none() {
+                    let id = state.notifications.push(
+                        Toast::new(ToastType::Info, 3000)
+                            .title("Exporting")
+                            .progress(Progress::Determinate(0.0)),
+                    );
+                    state.job = Some((id, 0.0));
+                }
+                ui.same_line();
+                if ui.button("Worker thread") {
+                    let sender = state.notifications.sender();
+                    thread::spawn(move || {
+                        let handle = sender.push(
+                            Toast::new(ToastType::Info, 3000)
+                                .title("Working")
+                                .progress(Progress::Indeterminate),
+                        );
+                        thread::sleep(Duration::from_secs(1));
+                        for i in 0..=10 {
+                            handle.set_progress(Progress::Determinate(i as f32 / 10.0));
+                            thread::sleep(Duration::from_millis(200));
+                        }
+                        handle.set_title("Work done");
+                        handle.complete(ToastType::Success);
+                    });
+                }
+                ui.same_line();
+                if ui.button("Slide in") {
+                    state.notifications.push(
+                        Toast::new(ToastType::Success, 3000)
+                            .content("Slid in from the edge")
+                            .animation(Animation::Slide, Animation::Slide)
+                            .easing(Easing::Back)
+                            .animation_time(400, 300),
+                    );
+                }
+                ui.same_line();
+                if ui.button("Sticky") {
+                    state.notifications.push(
+                        Toast::new(ToastType::Warning, 0)
+                            .content("Stays until closed")
+                            .sticky(),
+                    );
+                }
+                ui.same_line();
+                if ui.button("Custom type") {
+                    state
+                        .notifications
+                        .push(Toast::new(state.deploy, 3000).content("Version 1.2 is live"));
+                }
+                ui.same_line();
+                if ui.button("Rich content") {
+                    state.notifications.push(
+                        Toast::new(ToastType::Info, 8000)
+                            .title("Release notes")
+                            .markup(
+                                "**Version 1.2** is out with {#ff8800}faster{/} rendering.\n\n\
+                                 Read the [changelog](https://example.com/changelog).",
+                            )
+                            .custom(|ui| {
+                                ui.text_disabled("Widgets can go here too");
+                            }),
+                    );
+                }
+                if ui.checkbox("Light theme", &mut state.light_theme) {
+                    let (theme, deploy) = theme(state.light_theme);
+                    state.notifications.config().theme = theme;
+                    state.deploy = deploy;
+                }
+            }
+
+            if ui.collapsing_header("Dialogs", TreeNodeFlags::DEFAULT_OPEN) {
+                if ui.button("Alert") {
+                    state.notifications.dialog(Dialog::alert(
+                        ToastType::Error,
+                        "The file could not be saved.",
+                    ));
+                }
+                ui.same_line();
+                if ui.button("Confirm") {
+                    state.notifications.dialog(
+                        Dialog::confirm(ToastType::Warning, "Save changes before closing?")
+                            .title("Unsaved changes")
+                            .on_result(|result| println!("Answered {result:?}")),
+                    );
+                }
+                ui.same_line();
+                if ui.button("Prompt") {
+                    state.rename = Some(
+                        state.notifications.dialog(
+                            Dialog::prompt(ToastType::None, "New name of the file")
+                                .title("Rename")
+                                .text("notes.txt")
+                                .validate(|name| match name.trim() {
+                                    "" => Err("The name can't be empty".to_string()),
+                                    name if name.contains('/') => {
+                                        Err("The name can't contain '/'".to_string())
+                                    }
+                                    _ => Ok(()),
+                                }),
+                        ),
+                    );
+                }
+            }
+
+            let unread = state.notifications.history().unread();
+            if ui.button(format!("History ({unread})")) {
+                state.show_history = true;
+            }
+
             if ui.collapsing_header("Do it yourself", TreeNodeFlags::DEFAULT_OPEN) {
                 ui.input_text_multiline("Title", &mut state.title, [0.0, 0.0]).build();
                 ui.input_text_multiline("Content", &mut state.content, [0.0, 0.0])
@@ -67,18 +219,18 @@ fn draw(ui: &mut Ui, state: &mut State) {
 
                 let typ_str = ["None", "Success", "Warning", "Error", "Info"];
 
-                ui.combo_simple_string(
-                    "Type",
-                    unsafe { &mut *(&mut state.typ as *mut _ as *mut usize) },
-                    &typ_str,
-                );
+                ui.combo_simple_string("Type", &mut state.typ, &typ_str);
 
                 ui.checkbox("Enable title", &mut state.enable_title);
                 ui.same_line();
                 ui.checkbox("Enable content", &mut state.enable_content);
+                ui.same_line();
+                ui.checkbox("Closable", &mut state.closable);
 
                 if ui.button("Show") {
-                    let mut toast = Toast::new(state.typ, state.duration as usize);
+                    let mut toast = Toast::new(ToastType::ALL[state.typ], state.duration as usize)
+                        .closable(state.closable)
+                        .dismiss_on_click(true);
 
                     if state.enable_title {
                         toast = toast.title(state.title.clone());
@@ -93,5 +245,46 @@ fn draw(ui: &mut Ui, state: &mut State) {
             }
         });
 
-    state.notifications.render(ui);
+    if state.show_history {
+        state
+            .notifications
+            .history()
+            .draw_window(ui, &mut state.show_history);
+    }
+
+    if let Some((id, progress)) = &mut state.job {
+        *progress += ui.io().delta_time * 0.25;
+        if let Some(toast) = state.notifications.get_mut(*id) {
+            if *progress < 1.0 {
+                toast.set_progress(Progress::Determinate(*progress));
+            } else {
+                toast.set_title("Export finished");
+                toast.complete(ToastType::Success);
+                state.job = None;
+            }
+        } else {
+            state.job = None;
+        }
+    }
+
+    if let Some(result) = state.rename.as_ref().and_then(DialogHandle::result) {
+        if let DialogResult::Text(name) = result {
+            state
+                .notifications
+                .push(Toast::new(ToastType::Success, 3000).content(format!("Renamed to {name}")));
+        }
+        state.rename = None;
+    }
+
+    for event in state.notifications.render(ui) {
+        match event {
+            ToastEvent::Action(_, ACTION_UNDO) => {
+                state
+                    .notifications
+                    .push(Toast::new(ToastType::Success, 3000).content("File restored"));
+            }
+            ToastEvent::Link(_, url) => println!("Opening {url}"),
+            _ => {}
+        }
+    }
 }
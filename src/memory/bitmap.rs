@@ -0,0 +1,521 @@
+use super::{source::PageCache, MemorySource};
+use imgui::{
+    ComboBoxFlags, Condition, InputTextFlags, MouseButton, StyleColor, TextureId, Ui, WindowFlags,
+};
+use std::ops::Range;
+
+const MAX_SIZE: i32 = 4096;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum PixelFormat {
+    Indexed1,
+    Indexed2,
+    Indexed4,
+    Indexed8,
+    Rgb565,
+    Rgba8888,
+    /// 8x8 tiles, 4 bits per pixel, low nibble first
+    Gba4bpp,
+    /// 8x8 tiles, 8 bits per pixel
+    Gba8bpp,
+    /// 8x8 tiles, 2 interleaved bitplanes
+    Snes2bpp,
+    /// 8x8 tiles, 4 bitplanes stored as two 2bpp halves
+    Snes4bpp,
+}
+
+impl PixelFormat {
+    pub const ALL: [Self; 10] = [
+        Self::Indexed1,
+        Self::Indexed2,
+        Self::Indexed4,
+        Self::Indexed8,
+        Self::Rgb565,
+        Self::Rgba8888,
+        Self::Gba4bpp,
+        Self::Gba8bpp,
+        Self::Snes2bpp,
+        Self::Snes4bpp,
+    ];
+
+    fn desc(&self) -> &'static str {
+        match self {
+            PixelFormat::Indexed1 => "1bpp indexed",
+            PixelFormat::Indexed2 => "2bpp indexed",
+            PixelFormat::Indexed4 => "4bpp indexed",
+            PixelFormat::Indexed8 => "8bpp indexed",
+            PixelFormat::Rgb565 => "RGB565",
+            PixelFormat::Rgba8888 => "RGBA8888",
+            PixelFormat::Gba4bpp => "GBA 4bpp tiles",
+            PixelFormat::Gba8bpp => "GBA 8bpp tiles",
+            PixelFormat::Snes2bpp => "SNES 2bpp tiles",
+            PixelFormat::Snes4bpp => "SNES 4bpp tiles",
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Indexed1 => 1,
+            PixelFormat::Indexed2 | PixelFormat::Snes2bpp => 2,
+            PixelFormat::Indexed4 | PixelFormat::Gba4bpp | PixelFormat::Snes4bpp => 4,
+            PixelFormat::Indexed8 | PixelFormat::Gba8bpp => 8,
+            PixelFormat::Rgb565 => 16,
+            PixelFormat::Rgba8888 => 32,
+        }
+    }
+
+    fn is_tiled(&self) -> bool {
+        matches!(
+            self,
+            PixelFormat::Gba4bpp
+                | PixelFormat::Gba8bpp
+                | PixelFormat::Snes2bpp
+                | PixelFormat::Snes4bpp
+        )
+    }
+}
+
+/// Interprets a region of memory as an image.
+pub struct BitmapView {
+    addr: usize,
+    width: i32,
+    height: i32,
+    stride: i32,
+    format: PixelFormat,
+    zoom: i32,
+    palette: Vec<[u8; 4]>,
+    addr_input_buf: String,
+    pixels: Vec<[u8; 4]>,
+    hovered: Vec<Range<usize>>,
+    texture_id: Option<TextureId>,
+    #[cfg(feature = "renderer")]
+    texture_size: [u32; 2],
+    #[cfg(feature = "renderer")]
+    texture_dirty: bool,
+}
+
+impl Default for BitmapView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitmapView {
+    pub fn new() -> Self {
+        Self {
+            addr: 0,
+            width: 128,
+            height: 128,
+            stride: 0,
+            format: PixelFormat::Indexed8,
+            zoom: 2,
+            palette: Vec::new(),
+            addr_input_buf: String::with_capacity(32),
+            pixels: Vec::new(),
+            hovered: Vec::new(),
+            texture_id: None,
+            #[cfg(feature = "renderer")]
+            texture_size: [0, 0],
+            #[cfg(feature = "renderer")]
+            texture_dirty: false,
+        }
+    }
+
+    pub fn set_addr(&mut self, addr: usize) {
+        self.addr = addr;
+    }
+
+    /// Sets the width and height in pixels.
+    pub fn set_size(&mut self, width: i32, height: i32) {
+        self.width = width.clamp(1, MAX_SIZE);
+        self.height = height.clamp(1, MAX_SIZE);
+    }
+
+    /// Sets the distance in bytes between two rows of pixels, or rows of tiles for tiled
+    /// formats. `0` derives it from the width.
+    pub fn set_stride(&mut self, stride: i32) {
+        self.stride = stride.max(0);
+    }
+
+    pub fn set_format(&mut self, format: PixelFormat) {
+        self.format = format;
+    }
+
+    pub fn set_zoom(&mut self, zoom: i32) {
+        self.zoom = zoom.clamp(1, 16);
+    }
+
+    /// Sets the colors used by indexed formats. An empty palette uses a grayscale ramp.
+    pub fn set_palette(&mut self, palette: Vec<[u8; 4]>) {
+        self.palette = palette;
+    }
+
+    /// Bytes holding the pixel under the mouse, empty when there is none.
+    pub(super) fn hovered(&self) -> &[Range<usize>] {
+        &self.hovered
+    }
+
+    fn stride(&self) -> usize {
+        if self.stride > 0 {
+            return self.stride as usize;
+        }
+
+        let bpp = self.format.bits_per_pixel();
+        if self.format.is_tiled() {
+            self.width as usize / 8 * 8 * bpp
+        } else {
+            (self.width as usize * bpp).div_ceil(8)
+        }
+    }
+
+    /// Offset from `addr` of the first byte holding the pixel at `x`, `y` and the number of
+    /// bytes that hold it. SNES 4bpp pixels are also held by the two bytes 16 bytes later, see
+    /// [`BitmapView::pixel_ranges`].
+    fn pixel_span(&self, x: usize, y: usize) -> (usize, usize) {
+        let stride = self.stride();
+        let bpp = self.format.bits_per_pixel();
+        let tile = (y / 8) * stride + (x / 8) * 8 * bpp;
+        let row = tile + (y % 8) * bpp;
+
+        match self.format {
+            PixelFormat::Gba4bpp => (row + (x % 8) / 2, 1),
+            PixelFormat::Gba8bpp => (row + x % 8, 1),
+            PixelFormat::Snes2bpp | PixelFormat::Snes4bpp => (tile + (y % 8) * 2, 2),
+            _ => (y * stride + x * bpp / 8, (bpp / 8).max(1)),
+        }
+    }
+
+    /// Offsets from `addr` of all bytes holding the pixel at `x`, `y`.
+    fn pixel_ranges(&self, x: usize, y: usize) -> Vec<Range<usize>> {
+        let (off, len) = self.pixel_span(x, y);
+        let mut ranges = Vec::with_capacity(2);
+        ranges.push(off..off + len);
+        if self.format == PixelFormat::Snes4bpp {
+            // Planes 2 and 3 follow the 2bpp half of the tile
+            ranges.push(off + 16..off + 16 + len);
+        }
+        ranges
+    }
+
+    fn palette_color(&self, index: u8) -> [u8; 4] {
+        match self.palette.get(index as usize) {
+            Some(color) => *color,
+            None if self.palette.is_empty() => {
+                let max = (1u16 << self.format.bits_per_pixel().min(8)) - 1;
+                let v = (index as u16 * 255 / max) as u8;
+                [v, v, v, 255]
+            }
+            None => [0, 0, 0, 255],
+        }
+    }
+
+    fn decode_pixel(
+        &self,
+        x: usize,
+        y: usize,
+        mut read: impl FnMut(usize) -> Option<u8>,
+    ) -> Option<[u8; 4]> {
+        let (off, _) = self.pixel_span(x, y);
+        let bit = 7 - x % 8;
+
+        let index = match self.format {
+            PixelFormat::Indexed1 | PixelFormat::Indexed2 | PixelFormat::Indexed4 => {
+                let bpp = self.format.bits_per_pixel();
+                let shift = 8 - bpp - (x * bpp) % 8;
+                (read(off)? >> shift) & ((1 << bpp) - 1)
+            }
+            PixelFormat::Indexed8 | PixelFormat::Gba8bpp => read(off)?,
+            PixelFormat::Gba4bpp => {
+                let byte = read(off)?;
+                if x.is_multiple_of(2) {
+                    byte & 0xF
+                } else {
+                    byte >> 4
+                }
+            }
+            PixelFormat::Snes2bpp => {
+                let p0 = read(off)?;
+                let p1 = read(off + 1)?;
+                ((p0 >> bit) & 1) | ((p1 >> bit) & 1) << 1
+            }
+            PixelFormat::Snes4bpp => {
+                let p0 = read(off)?;
+                let p1 = read(off + 1)?;
+                let p2 = read(off + 16)?;
+                let p3 = read(off + 17)?;
+                ((p0 >> bit) & 1)
+                    | ((p1 >> bit) & 1) << 1
+                    | ((p2 >> bit) & 1) << 2
+                    | ((p3 >> bit) & 1) << 3
+            }
+            PixelFormat::Rgb565 => {
+                let v = u16::from_le_bytes([read(off)?, read(off + 1)?]);
+                let r = (v >> 11) as u8 & 0x1F;
+                let g = (v >> 5) as u8 & 0x3F;
+                let b = v as u8 & 0x1F;
+                return Some([r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]);
+            }
+            PixelFormat::Rgba8888 => {
+                return Some([read(off)?, read(off + 1)?, read(off + 2)?, read(off + 3)?]);
+            }
+        };
+
+        Some(self.palette_color(index))
+    }
+
+    fn decode<M: MemorySource + ?Sized>(&mut self, mem: &mut M, cache: &mut PageCache) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mem_size = mem.size();
+        let mut pixels = std::mem::take(&mut self.pixels);
+        pixels.clear();
+        pixels.reserve(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                // Unmapped bytes are left transparent
+                let pixel = self.decode_pixel(x, y, |off| {
+                    let addr = self.addr.checked_add(off).filter(|&addr| addr < mem_size)?;
+                    cache.get(mem, addr)
+                });
+                pixels.push(pixel.unwrap_or([0; 4]));
+            }
+        }
+
+        self.pixels = pixels;
+        #[cfg(feature = "renderer")]
+        {
+            self.texture_dirty = true;
+        }
+    }
+
+    /// Draws the view window. Returns the address of a pixel that was clicked.
+    pub(super) fn draw_window<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        mem: &mut M,
+        cache: &mut PageCache,
+        opened: &mut bool,
+        cursor_addr: usize,
+    ) -> Option<usize> {
+        let mut clicked = None;
+        self.hovered.clear();
+
+        ui.window("Bitmap View")
+            .size([400.0, 400.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                self.draw_options(ui, cursor_addr);
+                ui.separator();
+
+                self.decode(mem, cache);
+
+                ui.child_window("##pixels")
+                    .flags(WindowFlags::HORIZONTAL_SCROLLBAR)
+                    .build(|| clicked = self.draw_pixels(ui));
+            });
+
+        clicked
+    }
+
+    fn draw_options(&mut self, ui: &Ui, cursor_addr: usize) {
+        ui.set_next_item_width(ui.calc_text_size("0000000000000000")[0]);
+        if ui
+            .input_text("Address", &mut self.addr_input_buf)
+            .flags(InputTextFlags::CHARS_HEXADECIMAL | InputTextFlags::ENTER_RETURNS_TRUE)
+            .build()
+        {
+            if let Ok(addr) = usize::from_str_radix(&self.addr_input_buf, 16) {
+                self.addr = addr;
+            }
+        }
+        if cursor_addr != usize::MAX {
+            ui.same_line();
+            if ui.button("Use cursor") {
+                self.addr = cursor_addr;
+                self.addr_input_buf = format!("{cursor_addr:X}");
+            }
+        }
+
+        ui.set_next_item_width(ui.calc_text_size("RGBA8888 tiles")[0] * 1.5);
+        if let Some(_t) =
+            ui.begin_combo_with_flags("Format", self.format.desc(), ComboBoxFlags::HEIGHT_LARGEST)
+        {
+            for format in PixelFormat::ALL {
+                if ui
+                    .selectable_config(format.desc())
+                    .selected(self.format == format)
+                    .build()
+                {
+                    self.format = format;
+                }
+            }
+        }
+
+        let item_width = ui.calc_text_size("000000")[0] * 2.0;
+        ui.set_next_item_width(item_width);
+        if ui.input_int("Width", &mut self.width).step(8).build() {
+            self.width = self.width.clamp(1, MAX_SIZE);
+        }
+        ui.same_line();
+        ui.set_next_item_width(item_width);
+        if ui.input_int("Height", &mut self.height).step(8).build() {
+            self.height = self.height.clamp(1, MAX_SIZE);
+        }
+        ui.set_next_item_width(item_width);
+        if ui.input_int("Stride", &mut self.stride).build() {
+            self.stride = self.stride.max(0);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Bytes per row, 0 to derive it from the width");
+        }
+        ui.same_line();
+        ui.set_next_item_width(item_width);
+        ui.slider("Zoom", 1, 16, &mut self.zoom);
+
+        if self.format.is_tiled() {
+            // Tiled formats are laid out in whole 8x8 tiles
+            self.width = (self.width + 7) / 8 * 8;
+            self.height = (self.height + 7) / 8 * 8;
+        }
+    }
+
+    fn draw_pixels(&mut self, ui: &Ui) -> Option<usize> {
+        let zoom = self.zoom as f32;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let pos = ui.cursor_screen_pos();
+        let size = [width as f32 * zoom, height as f32 * zoom];
+
+        ui.invisible_button("##image", size);
+        let hovered = ui.is_item_hovered();
+        let draw_list = ui.get_window_draw_list();
+
+        match self.texture_id {
+            Some(texture_id) => {
+                draw_list
+                    .add_image(texture_id, pos, [pos[0] + size[0], pos[1] + size[1]])
+                    .build();
+            }
+            None => {
+                // No texture uploaded, draw pixels that are on screen as rectangles
+                let clip_min = ui.window_pos();
+                let clip_max = [
+                    clip_min[0] + ui.window_size()[0],
+                    clip_min[1] + ui.window_size()[1],
+                ];
+                let x_range = ((clip_min[0] - pos[0]) / zoom).max(0.0) as usize
+                    ..(((clip_max[0] - pos[0]) / zoom).max(0.0) as usize + 1).min(width);
+                let y_range = ((clip_min[1] - pos[1]) / zoom).max(0.0) as usize
+                    ..(((clip_max[1] - pos[1]) / zoom).max(0.0) as usize + 1).min(height);
+
+                for y in y_range {
+                    for x in x_range.clone() {
+                        let [r, g, b, a] = self.pixels[y * width + x];
+                        if a == 0 {
+                            continue;
+                        }
+                        let min = [pos[0] + x as f32 * zoom, pos[1] + y as f32 * zoom];
+                        draw_list
+                            .add_rect(
+                                min,
+                                [min[0] + zoom, min[1] + zoom],
+                                [
+                                    r as f32 / 255.0,
+                                    g as f32 / 255.0,
+                                    b as f32 / 255.0,
+                                    a as f32 / 255.0,
+                                ],
+                            )
+                            .filled(true)
+                            .build();
+                    }
+                }
+            }
+        }
+
+        if !hovered {
+            return None;
+        }
+
+        let mouse = ui.io().mouse_pos;
+        let x = (((mouse[0] - pos[0]) / zoom) as usize).min(width - 1);
+        let y = (((mouse[1] - pos[1]) / zoom) as usize).min(height - 1);
+        let addr = self.addr.saturating_add(self.pixel_span(x, y).0);
+        self.hovered = self
+            .pixel_ranges(x, y)
+            .into_iter()
+            .map(|range| self.addr.saturating_add(range.start)..self.addr.saturating_add(range.end))
+            .collect();
+
+        let min = [pos[0] + x as f32 * zoom, pos[1] + y as f32 * zoom];
+        draw_list
+            .add_rect(
+                min,
+                [min[0] + zoom, min[1] + zoom],
+                ui.style_color(StyleColor::NavHighlight),
+            )
+            .build();
+
+        let [r, g, b, a] = self.pixels[y * width + x];
+        ui.tooltip_text(format!(
+            "{x}, {y}\nAddress {addr:X}\nRGBA {r:02X}{g:02X}{b:02X}{a:02X}"
+        ));
+
+        if ui.is_mouse_clicked(MouseButton::Left) {
+            Some(addr)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "renderer")]
+impl BitmapView {
+    /// Uploads the last decoded image to a texture so it is drawn as a single image.
+    pub(super) fn upload(&mut self, gfx: &mut crate::gfx::GfxContext) {
+        use crate::texture::{TextureDescriptor, TextureRange};
+
+        if !self.texture_dirty || self.pixels.is_empty() {
+            return;
+        }
+        self.texture_dirty = false;
+
+        let size = [self.width as u32, self.height as u32];
+        let data = self.pixels.concat();
+
+        if let Some(texture) = self
+            .texture_id
+            .filter(|_| self.texture_size == size)
+            .and_then(|id| gfx.renderer.textures.get_mut(&id))
+        {
+            texture.set_data(&gfx.queue, &data, TextureRange::default());
+            return;
+        }
+
+        if let Some(texture_id) = self.texture_id.take() {
+            gfx.renderer.remove_texture(texture_id);
+        }
+
+        let mut texture = gfx.renderer.create_texture(
+            &gfx.device,
+            &wgpu::SamplerDescriptor {
+                label: Some("memory bitmap sampler"),
+                min_filter: wgpu::FilterMode::Nearest,
+                mag_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            },
+            TextureDescriptor {
+                label: Some("memory bitmap".to_string()),
+                size: wgpu::Extent3d {
+                    width: size[0],
+                    height: size[1],
+                    depth_or_array_layers: 1,
+                },
+                format: Some(wgpu::TextureFormat::Rgba8Unorm),
+                ..Default::default()
+            },
+        );
+        texture.set_data(&gfx.queue, &data, TextureRange::default());
+        self.texture_id = Some(gfx.renderer.insert_texture(texture));
+        self.texture_size = size;
+    }
+}
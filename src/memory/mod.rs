@@ -5,110 +5,123 @@ use imgui::{
         igGetTextLineHeightWithSpacing, igSetCursorPosX, igSetWindowSizeVec2,
     },
     ComboBoxFlags, Condition, InputTextCallback, InputTextCallbackHandler, InputTextFlags, Key,
-    ListClipper, MouseButton, StyleColor, StyleVar, Ui, WindowFlags, WindowHoveredFlags,
+    MouseButton, StyleColor, StyleVar, Ui, WindowFlags, WindowHoveredFlags,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::transmute,
+    ops::Range,
 };
-use std::mem::{size_of, transmute};
 
-#[derive(PartialEq, Clone, Copy)]
-enum DataFormat {
-    Bin,
-    Dec,
-    Hex,
-}
+mod bitmap;
+mod source;
+mod stats;
+mod style;
+mod template;
+mod value;
+mod watch;
+mod watchpoint;
+
+pub use bitmap::{BitmapView, PixelFormat};
+pub use source::MemorySource;
+use source::PageCache;
+pub use stats::ByteStats;
+use stats::{StatsPanel, ENTROPY_STRIP_WIDTH};
+pub use style::MemoryEditorStyle;
+pub use template::{Node, Template, TemplateError, TemplateView, Value};
+use value::format_value;
+pub use value::{DataFormat, DataType, Endianness};
+pub use watch::{ParseWatchListError, Watch, WatchList, WatchTarget};
+use watchpoint::HIT_COLOR;
+pub use watchpoint::{
+    Access, Comparison, ValueCondition, Watchpoint, WatchpointHit, WatchpointId, WatchpointKind,
+    Watchpoints,
+};
+
+const WHEEL_SCROLL_LINES: f32 = 3.0;
+const MAX_AUTO_FIT_COLS: usize = 256;
 
-#[derive(PartialEq, Clone, Copy)]
-enum DataType {
-    I8,
-    I16,
-    I32,
-    I64,
-    U8,
-    U16,
-    U32,
-    U64,
-    F32,
-    F64,
+type HighligtFn<T> = fn(data: &T, off: usize);
+type WritableFn = fn(addr: usize) -> bool;
+
+/// How the address column labels rows.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AddressMode {
+    /// The base address plus the offset, see [`MemoryEditor::set_base_addr`]
+    Absolute,
+    /// The offset into the memory source
+    Offset,
+    Hidden,
 }
 
-impl DataType {
-    const ALL: [Self; 10] = [
-        Self::I8,
-        Self::I16,
-        Self::I32,
-        Self::I64,
-        Self::U8,
-        Self::U16,
-        Self::U32,
-        Self::U64,
-        Self::F32,
-        Self::F64,
-    ];
-
-    fn size(&self) -> usize {
-        match self {
-            DataType::I8 => size_of::<i8>(),
-            DataType::I16 => size_of::<i16>(),
-            DataType::I32 => size_of::<i32>(),
-            DataType::I64 => size_of::<i64>(),
-            DataType::U8 => size_of::<u8>(),
-            DataType::U16 => size_of::<u16>(),
-            DataType::U32 => size_of::<u32>(),
-            DataType::U64 => size_of::<u64>(),
-            DataType::F32 => size_of::<f32>(),
-            DataType::F64 => size_of::<f64>(),
-        }
-    }
+impl AddressMode {
+    pub const ALL: [Self; 3] = [Self::Absolute, Self::Offset, Self::Hidden];
 
     fn desc(&self) -> &'static str {
         match self {
-            DataType::I8 => "i8",
-            DataType::I16 => "i16",
-            DataType::I32 => "i32",
-            DataType::I64 => "i64",
-            DataType::U8 => "u8",
-            DataType::U16 => "u16",
-            DataType::U32 => "u32",
-            DataType::U64 => "u64",
-            DataType::F32 => "f32",
-            DataType::F64 => "f64",
+            AddressMode::Absolute => "Absolute",
+            AddressMode::Offset => "Offset",
+            AddressMode::Hidden => "Hidden",
         }
     }
 }
 
-type ReadFn<T> = fn(data: &T, off: usize);
-type WriteFn<T> = fn(data: &mut T, off: usize, val: u8);
-type HighligtFn<T> = fn(data: &T, off: usize);
-
 pub struct MemoryEditor {
     contents_width_changed: bool,
     data_preview_adr: usize,
     data_editing_addr: usize,
     data_editing_take_focus: bool,
+    data_editing_ascii: bool,
+    scroll_to_cursor: bool,
+    scroll_line: usize,
+    scrollbar_grab_offset: f32,
+    /// Fraction of a line scrolled by the wheel, carried over to the next frame
+    wheel_remainder: f32,
+    cache: PageCache,
+    bitmap: BitmapView,
+    stats: StatsPanel,
+    template: TemplateView,
+    watches: WatchList,
+    watchpoints: Watchpoints,
+    labels: HashMap<String, usize>,
+    staged: BTreeMap<usize, u8>,
     data_input_buf: String,
     addr_input_buf: String,
     goto_addr: usize,
     highlight_min: usize,
     highlight_max: usize,
-    preview_endianess: i32,
+    preview_endianess: Endianness,
     preview_data_type: DataType,
+    /// Width of the window last frame, what auto-fit columns are fitted to
+    fit_width: f32,
 
     // Settings
     open: bool,
     read_only: bool,
+    confirm_writes: bool,
     cols: i32,
+    auto_fit_cols: bool,
+    address_mode: AddressMode,
+    base_addr: usize,
+    show_header: bool,
     show_options: bool,
     show_data_preview: bool,
     show_hexii: bool,
     show_ascii: bool,
+    show_bitmap: bool,
+    show_stats: bool,
+    show_template: bool,
+    show_watches: bool,
+    show_watchpoints: bool,
     grey_out_zeros: bool,
     uppercase_hex: bool,
     mid_cols_count: usize,
+    mid_cols_lines: bool,
     addr_digits_count: usize,
     footer_extra_height: f32,
-    highlight_color: [f32; 4],
-    read_fn: Option<ReadFn<[u8]>>,
-    write_fn: Option<WriteFn<[u8]>>,
+    style: MemoryEditorStyle,
     highlight_fn: Option<HighligtFn<[u8]>>,
+    writable_fn: Option<WritableFn>,
 }
 
 impl MemoryEditor {
@@ -118,38 +131,281 @@ impl MemoryEditor {
             data_preview_adr: usize::MAX,
             data_editing_addr: usize::MAX,
             data_editing_take_focus: false,
+            data_editing_ascii: false,
+            scroll_to_cursor: false,
+            scroll_line: 0,
+            scrollbar_grab_offset: 0.0,
+            wheel_remainder: 0.0,
+            cache: PageCache::default(),
+            bitmap: BitmapView::new(),
+            stats: StatsPanel::new(),
+            template: TemplateView::new(),
+            watches: WatchList::new(),
+            watchpoints: Watchpoints::new(),
+            labels: HashMap::new(),
+            staged: BTreeMap::new(),
             data_input_buf: String::with_capacity(32),
             addr_input_buf: String::with_capacity(32),
             goto_addr: usize::MAX,
             highlight_min: usize::MAX,
             highlight_max: usize::MAX,
-            preview_endianess: 0,
+            preview_endianess: Endianness::Little,
             preview_data_type: DataType::I32,
+            fit_width: 0.0,
             open: true,
             read_only: false,
+            confirm_writes: false,
             cols: 16,
+            auto_fit_cols: false,
+            address_mode: AddressMode::Absolute,
+            base_addr: 0,
+            show_header: false,
             show_options: true,
             show_data_preview: false,
             show_hexii: false,
             show_ascii: true,
+            show_bitmap: false,
+            show_stats: false,
+            show_template: false,
+            show_watches: false,
+            show_watchpoints: false,
             grey_out_zeros: true,
             uppercase_hex: true,
             mid_cols_count: 8,
+            mid_cols_lines: false,
             addr_digits_count: 0,
             footer_extra_height: 0.0,
-            highlight_color: [255.0, 255.0, 255.0, 50.0],
-            read_fn: None,
-            write_fn: None,
+            style: MemoryEditorStyle::default(),
             highlight_fn: None,
+            writable_fn: None,
         }
     }
 
     pub fn open(&self) -> bool {
         self.open
     }
+
+    pub fn style(&mut self) -> &mut MemoryEditorStyle {
+        &mut self.style
+    }
+
+    pub fn set_style(&mut self, style: MemoryEditorStyle) {
+        self.style = style;
+        self.contents_width_changed = true;
+    }
+
+    /// Bytes per row, ignored while columns are fitted to the window.
+    pub fn set_cols(&mut self, cols: usize) {
+        self.set_cols_keep_scroll(cols.max(1));
+        self.contents_width_changed = true;
+    }
+
+    /// Fits as many columns as the window is wide, in whole groups of mid columns.
+    pub fn set_auto_fit_cols(&mut self, auto_fit: bool) {
+        self.auto_fit_cols = auto_fit;
+        self.contents_width_changed = true;
+    }
+
+    /// Every `count` columns get some extra spacing, 0 disables it. `lines` draws a separator in
+    /// the spacing.
+    pub fn set_mid_cols(&mut self, count: usize, lines: bool) {
+        self.mid_cols_count = count;
+        self.mid_cols_lines = lines;
+        self.contents_width_changed = true;
+    }
+
+    pub fn set_address_mode(&mut self, mode: AddressMode) {
+        self.address_mode = mode;
+        self.contents_width_changed = true;
+    }
+
+    /// Address of the first byte of memory, shown in [`AddressMode::Absolute`].
+    pub fn set_base_addr(&mut self, addr: usize) {
+        self.base_addr = addr;
+        self.contents_width_changed = true;
+    }
+
+    /// Shows a row labeling the offset of every column above the bytes.
+    pub fn set_show_header(&mut self, show: bool) {
+        self.show_header = show;
+    }
+
+    /// Changes the columns, keeping the top row's first byte in view.
+    fn set_cols_keep_scroll(&mut self, cols: usize) {
+        let top = self.scroll_line * self.cols as usize;
+        self.cols = cols as i32;
+        self.scroll_line = top / cols;
+    }
+
+    fn base_display_addr(&self) -> usize {
+        match self.address_mode {
+            AddressMode::Absolute => self.base_addr,
+            AddressMode::Offset | AddressMode::Hidden => 0,
+        }
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Decides per address whether it can be edited, on top of the read only flag.
+    pub fn set_writable_fn(&mut self, writable_fn: Option<WritableFn>) {
+        self.writable_fn = writable_fn;
+    }
+
+    /// Stages edits instead of writing them right away, until [`MemoryEditor::commit`] is called
+    /// or "Commit" is clicked.
+    pub fn set_confirm_writes(&mut self, confirm_writes: bool) {
+        self.confirm_writes = confirm_writes;
+    }
+
+    /// Whether there are staged edits that haven't been written yet.
+    pub fn is_dirty(&self) -> bool {
+        !self.staged.is_empty()
+    }
+
+    /// Writes all staged edits.
+    pub fn commit<M: MemorySource + ?Sized>(&mut self, mem: &mut M) {
+        for (addr, val) in std::mem::take(&mut self.staged) {
+            mem.write(addr, val);
+            self.cache.invalidate(addr);
+        }
+    }
+
+    /// Drops all staged edits.
+    pub fn discard(&mut self) {
+        self.staged.clear();
+    }
+
+    fn is_writable(&self, addr: usize) -> bool {
+        !self.read_only && !self.is_protected(addr)
+    }
+
+    /// Whether the writable function refuses `addr`, regardless of the read only flag.
+    fn is_protected(&self, addr: usize) -> bool {
+        self.writable_fn.is_some_and(|f| !f(addr))
+    }
+
+    fn write_byte<M: MemorySource + ?Sized>(&mut self, mem: &mut M, addr: usize, val: u8) {
+        if !self.is_writable(addr) {
+            return;
+        }
+        if self.confirm_writes {
+            self.staged.insert(addr, val);
+        } else {
+            mem.write(addr, val);
+            self.cache.invalidate(addr);
+        }
+    }
+
+    /// Writes all of `bytes` starting at `addr`, or none of them if any can't be written.
+    fn write_bytes<M: MemorySource + ?Sized>(
+        &mut self,
+        mem: &mut M,
+        addr: usize,
+        bytes: &[u8],
+    ) -> bool {
+        if !(0..bytes.len()).all(|i| self.is_writable(addr.wrapping_add(i))) {
+            return false;
+        }
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.write_byte(mem, addr.wrapping_add(i), byte);
+        }
+        true
+    }
+
+    /// The byte at `addr` with staged edits applied.
+    fn read_byte<M: MemorySource + ?Sized>(&mut self, mem: &mut M, addr: usize) -> Option<u8> {
+        match self.staged.get(&addr) {
+            Some(&val) => Some(val),
+            None => self.cache.get(mem, addr),
+        }
+    }
+
+    /// Fills `buf` from `addr` with staged edits applied, fails if any byte is unmapped or past
+    /// the end of `mem`.
+    fn read_bytes<M: MemorySource + ?Sized>(
+        &mut self,
+        mem: &mut M,
+        addr: usize,
+        buf: &mut [u8],
+    ) -> bool {
+        let size = mem.size();
+        if addr
+            .checked_add(buf.len())
+            .filter(|&end| end <= size)
+            .is_none()
+        {
+            return false;
+        }
+        for (i, byte) in buf.iter_mut().enumerate() {
+            match self.read_byte(mem, addr + i) {
+                Some(val) => *byte = val,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    pub fn bitmap_view(&mut self) -> &mut BitmapView {
+        &mut self.bitmap
+    }
+
+    pub fn template_view(&mut self) -> &mut TemplateView {
+        &mut self.template
+    }
+
+    pub fn watches(&mut self) -> &mut WatchList {
+        &mut self.watches
+    }
+
+    /// Watchpoints set from the UI, for the host to check its memory accesses against.
+    pub fn watchpoints(&mut self) -> &mut Watchpoints {
+        &mut self.watchpoints
+    }
+
+    /// Called by the host when an access matched a watchpoint,
+    /// the accessed bytes get highlighted and the view jumps to them.
+    pub fn report_watchpoint_hit(&mut self, hit: WatchpointHit) {
+        if self.watchpoints.report(hit) {
+            self.goto_addr = hit.addr;
+        }
+    }
+
+    /// Names an address, so watches can refer to it by label.
+    pub fn set_label(&mut self, name: &str, addr: usize) {
+        self.labels.insert(name.to_owned(), addr);
+    }
+
+    pub fn remove_label(&mut self, name: &str) {
+        self.labels.remove(name);
+    }
+
+    /// The highlighted range, or the bytes of the data preview at the cursor.
+    fn selection(&self) -> Option<Range<usize>> {
+        if self.highlight_min < self.highlight_max && self.highlight_max != usize::MAX {
+            Some(self.highlight_min..self.highlight_max)
+        } else if self.data_preview_adr != usize::MAX {
+            let size = self.preview_data_type.size().max(1);
+            Some(self.data_preview_adr..self.data_preview_adr.saturating_add(size))
+        } else {
+            None
+        }
+    }
+
+    /// Uploads the bitmap view to a texture, call this once per frame after drawing, for example
+    /// from the callback of `Framework::run_with_gfx`. Without it the bitmap view falls back to
+    /// drawing every pixel as a rectangle.
+    #[cfg(feature = "renderer")]
+    pub fn upload_textures(&mut self, gfx: &mut crate::gfx::GfxContext) {
+        if self.show_bitmap {
+            self.bitmap.upload(gfx);
+        }
+    }
 }
 
 struct Sizes {
+    cols: usize,
     addr_digit_count: usize,
     line_height: f32,
     glyph_width: f32,
@@ -159,6 +415,7 @@ struct Sizes {
     pos_hex_end: f32,
     pos_ascii_start: f32,
     pos_ascii_end: f32,
+    header_height: f32,
     window_width: f32,
 }
 
@@ -168,7 +425,7 @@ impl MemoryEditor {
         let mut s: Sizes = zero();
         s.addr_digit_count = self.addr_digits_count;
         if s.addr_digit_count == 0 {
-            let mut n = base_display_addr + mem_size - 1;
+            let mut n = base_display_addr.saturating_add(mem_size.saturating_sub(1));
             while n > 0 {
                 s.addr_digit_count += 1;
                 n >>= 4;
@@ -178,29 +435,77 @@ impl MemoryEditor {
         s.glyph_width = ui.calc_text_size("F")[0] + 1.0; // We assume the font is mono-space
         s.hex_cell_width = (s.glyph_width * 2.5) as i32 as f32; // "FF " we include trailing space in the width to easily catch clicks everywhere
         s.spacing_between_mid_cols = (s.hex_cell_width * 0.25) as i32 as f32; // Every OptMidColsCount columns we add a bit of extra spacing
-        s.pos_hex_start = (s.addr_digit_count + 2) as f32 * s.glyph_width;
-        s.pos_hex_end = s.pos_hex_start + (s.hex_cell_width * self.cols as f32);
+        s.pos_hex_start = if self.address_mode == AddressMode::Hidden {
+            0.0
+        } else {
+            (s.addr_digit_count + 2) as f32 * s.glyph_width
+        };
+        s.header_height = if self.show_header { s.line_height } else { 0.0 };
+
+        // Everything right of the columns
+        let mut margin = style.scrollbar_size + style.window_padding[0] * 2.0 + s.glyph_width;
+        if self.show_stats {
+            margin += ENTROPY_STRIP_WIDTH;
+        }
+
+        s.cols = self.cols as usize;
+        if self.auto_fit_cols && self.fit_width > 0.0 {
+            let avail = self.fit_width - s.pos_hex_start - margin;
+            s.cols = (2..=MAX_AUTO_FIT_COLS)
+                .take_while(|&cols| self.cols_width(&s, cols) <= avail)
+                .last()
+                .unwrap_or(1);
+            if self.mid_cols_count > 0 && s.cols > self.mid_cols_count {
+                s.cols -= s.cols % self.mid_cols_count;
+            }
+        }
+
+        s.pos_hex_end = s.pos_hex_start + (s.hex_cell_width * s.cols as f32);
         s.pos_ascii_start = s.pos_hex_end;
         s.pos_ascii_end = s.pos_hex_end;
         if self.show_ascii {
             s.pos_ascii_start = s.pos_hex_end + s.glyph_width * 1.0;
-            if self.mid_cols_count > 0 {
-                s.pos_ascii_start += ((self.cols as usize + self.mid_cols_count - 1)
-                    / self.mid_cols_count as usize) as f32
-                    * s.spacing_between_mid_cols;
-            }
-            s.pos_ascii_end = s.pos_ascii_start + self.cols as f32 * s.glyph_width;
+            s.pos_ascii_start += self.mid_col_groups(s.cols) as f32 * s.spacing_between_mid_cols;
+            s.pos_ascii_end = s.pos_ascii_start + s.cols as f32 * s.glyph_width;
         }
-        s.window_width =
-            s.pos_ascii_end + style.scrollbar_size + style.window_padding[0] * 2.0 + s.glyph_width;
+        s.window_width = s.pos_ascii_end + margin;
         s
     }
 
-    pub fn draw_window(&mut self, ui: &Ui, data: &mut [u8]) {
-        let base_display_addr = 0x0000;
+    /// Width of `cols` columns of hex and ascii.
+    fn cols_width(&self, s: &Sizes, cols: usize) -> f32 {
+        let mut width = s.hex_cell_width * cols as f32;
+        if self.show_ascii {
+            width += s.glyph_width * (cols + 1) as f32;
+            width += self.mid_col_groups(cols) as f32 * s.spacing_between_mid_cols;
+        }
+        width
+    }
+
+    /// Number of mid column groups `cols` columns are split into, 0 without groups.
+    fn mid_col_groups(&self, cols: usize) -> usize {
+        match self.mid_cols_count {
+            0 => 0,
+            count => cols.div_ceil(count),
+        }
+    }
+
+    /// Position of the `n`th hex column relative to the scrolling region.
+    fn hex_pos_x(&self, s: &Sizes, n: usize) -> f32 {
+        let mut x = s.pos_hex_start + s.hex_cell_width * n as f32;
+        if let Some(groups) = n.checked_div(self.mid_cols_count) {
+            x += groups as f32 * s.spacing_between_mid_cols;
+        }
+        x
+    }
+
+    pub fn draw_window<M: MemorySource + ?Sized>(&mut self, ui: &Ui, mem: &mut M) {
+        let base_display_addr = self.base_display_addr();
 
-        let mem_size = data.len();
+        let mem_size = mem.size();
 
+        // The layout is measured with the font, so it's pushed before computing sizes
+        let font = self.style.font.map(|font| ui.push_font(font));
         let mut size = unsafe { self.calc_sizes(ui, mem_size, base_display_addr) };
 
         self.open = true;
@@ -210,7 +515,17 @@ impl MemoryEditor {
                 [size.window_width, size.window_width * 0.6],
                 Condition::FirstUseEver,
             )
-            .size_constraints([0.0, 0.0], [size.window_width, f32::MAX])
+            .size_constraints(
+                [0.0, 0.0],
+                [
+                    if self.auto_fit_cols {
+                        f32::MAX
+                    } else {
+                        size.window_width
+                    },
+                    f32::MAX,
+                ],
+            )
             .opened(unsafe { transmute(&mut self.open) })
             .flags(WindowFlags::NO_SCROLLBAR)
             .build(|| {
@@ -220,23 +535,77 @@ impl MemoryEditor {
                     ui.open_popup("context")
                 }
 
+                self.fit_width = ui.window_size()[0];
                 unsafe {
-                    self.draw_contents(ui, data, mem_size, base_display_addr);
+                    self.draw_contents(ui, mem, mem_size, base_display_addr);
                 }
 
-                if self.contents_width_changed {
+                if self.contents_width_changed && !self.auto_fit_cols {
                     unsafe {
                         size = self.calc_sizes(ui, mem_size, base_display_addr);
                         igSetWindowSizeVec2([size.window_width, ui.window_size()[1]].into(), 0);
                     }
                 }
+                self.contents_width_changed = false;
             });
+
+        if let Some(font) = font {
+            font.pop();
+        }
+
+        if self.show_bitmap {
+            if let Some(addr) = self.bitmap.draw_window(
+                ui,
+                mem,
+                &mut self.cache,
+                &mut self.show_bitmap,
+                self.data_preview_adr,
+            ) {
+                self.goto_addr = addr;
+            }
+        }
+
+        if self.show_stats {
+            let selection = self.selection();
+            self.stats
+                .draw_window(ui, mem, &mut self.show_stats, selection);
+            if !self.show_stats {
+                self.contents_width_changed = true;
+            }
+        }
+
+        if self.show_template {
+            if let Some(addr) = self.template.draw_window(ui, mem, &mut self.show_template) {
+                self.goto_addr = addr;
+            }
+        }
+
+        if self.show_watches {
+            // Taken out so the list can read and write through the editor
+            let mut watches = std::mem::take(&mut self.watches);
+            let mut show_watches = self.show_watches;
+            let clicked = watches.draw_window(ui, self, mem, &mut show_watches);
+            self.watches = watches;
+            self.show_watches = show_watches;
+            if let Some(addr) = clicked {
+                self.goto_addr = addr;
+            }
+        }
+
+        if self.show_watchpoints {
+            if let Some(addr) =
+                self.watchpoints
+                    .draw_window(ui, &mut self.show_watchpoints, self.data_preview_adr)
+            {
+                self.goto_addr = addr;
+            }
+        }
     }
 
-    unsafe fn draw_contents(
+    unsafe fn draw_contents<M: MemorySource + ?Sized>(
         &mut self,
         ui: &Ui,
-        data: &mut [u8],
+        mem: &mut M,
         mem_size: usize,
         base_display_addr: usize,
     ) {
@@ -244,7 +613,13 @@ impl MemoryEditor {
             self.cols = 1;
         }
 
+        // Pages are fetched as rows get drawn and dropped every frame, so live memory stays current
+        self.cache.clear();
+
         let s = self.calc_sizes(ui, mem_size, base_display_addr);
+        if s.cols != self.cols as usize {
+            self.set_cols_keep_scroll(s.cols);
+        }
         let style = ui.style();
 
         // We begin into our scrolling region with the 'ImGuiWindowFlags_NoMove' in order to prevent click from moving the window.
@@ -259,46 +634,103 @@ impl MemoryEditor {
                 + igGetFrameHeightWithSpacing() * 1.0
                 + igGetTextLineHeightWithSpacing() * 3.0;
         }
+        let lock_show_staged = self.is_dirty();
+        if lock_show_staged {
+            footer_height += height_separator + igGetFrameHeightWithSpacing() * 1.0
+        }
+
+        if !self.show_ascii {
+            self.data_editing_ascii = false;
+        }
+
+        if self.goto_addr != usize::MAX {
+            if self.goto_addr < mem_size {
+                self.scroll_line = self.goto_addr / self.cols as usize;
+                self.data_editing_addr = self.goto_addr;
+                self.data_preview_adr = self.goto_addr;
+                self.data_editing_take_focus = true;
+            }
+            self.goto_addr = usize::MAX;
+        }
+
+        // PageUp/PageDown move by the number of rows visible in the scrolling region
+        let visible_rows = ((ui.content_region_avail()[1] - footer_height - s.header_height)
+            / s.line_height)
+            .max(1.0) as usize;
+        let cols = self.cols as usize;
+        let line_total_count = mem_size.div_ceil(cols);
 
         let mut data_next = false;
         let mut data_editing_addr_next = usize::MAX;
-        if self.data_editing_addr != usize::MAX {
-            if ui.is_key_pressed(Key::UpArrow)
-                && self.data_editing_addr as isize >= self.cols as isize
-            {
-                data_editing_addr_next = self.data_editing_addr - self.cols as usize;
-            } else if ui.is_key_pressed(Key::DownArrow)
-                && (self.data_editing_addr as isize) < ((mem_size - self.cols as usize) as isize)
-            {
-                data_editing_addr_next = self.data_editing_addr + self.cols as usize;
-            } else if ui.is_key_pressed(Key::LeftArrow)
-                && (self.data_editing_addr as isize) > 0isize
-            {
-                data_editing_addr_next = self.data_editing_addr - 1;
-            } else if ui.is_key_pressed(Key::RightArrow)
-                && (self.data_editing_addr as isize) < ((mem_size - 1) as isize)
-            {
-                data_editing_addr_next = self.data_editing_addr + 1;
+        if self.data_editing_addr != usize::MAX && self.data_editing_addr < mem_size {
+            if ui.is_key_pressed(Key::Tab) {
+                self.data_editing_ascii = self.show_ascii && !self.data_editing_ascii;
+                self.data_editing_take_focus = true;
+            } else if let Some(addr) = self.nav_target(ui, mem_size, visible_rows) {
+                data_editing_addr_next = addr;
             }
         }
 
         ui.child_window("##scrolling")
             .size([0.0, -footer_height])
             .border(false)
-            .flags(WindowFlags::NO_MOVE | WindowFlags::NO_NAV)
+            .flags(
+                WindowFlags::NO_MOVE
+                    | WindowFlags::NO_NAV
+                    | WindowFlags::NO_SCROLLBAR
+                    | WindowFlags::NO_SCROLL_WITH_MOUSE,
+            )
             .build(|| {
                 let draw_list = ui.get_window_draw_list();
+                let colors = self.style.colors(ui);
 
                 let _t1 = ui.push_style_var(StyleVar::FramePadding([0.0, 0.0]));
                 let _t2 = ui.push_style_var(StyleVar::ItemSpacing([0.0, 0.0]));
 
-                // We are not really using the clipper API correctly here, because we rely on visible_start_addr/visible_end_addr for our scrolling function.
-                let line_total_count =
-                    ((mem_size + self.cols as usize - 1) / self.cols as usize) as i32;
-                let _clipper = ListClipper::new(line_total_count).items_height(s.line_height);
-                let mut clipper = _clipper.begin(ui);
+                // We do the scrolling ourselves in whole lines, imgui's scroll offset is a f32 pixel
+                // position that loses precision long before the end of a large address space.
+                // Rows are always laid out from the top of the region instead.
+                ui.set_scroll_y(0.0);
+                if ui.is_window_hovered() {
+                    // Touchpads scroll by fractions of a notch, they add up over frames
+                    let wheel = ui.io().mouse_wheel * WHEEL_SCROLL_LINES + self.wheel_remainder;
+                    self.wheel_remainder = wheel.fract();
+                    let lines = wheel.trunc() as isize;
+                    if lines > 0 {
+                        self.scroll_line = self.scroll_line.saturating_sub(lines as usize);
+                    } else {
+                        self.scroll_line = self.scroll_line.saturating_add(-lines as usize);
+                    }
+                }
 
-                if self.read_only || self.data_editing_addr >= mem_size {
+                // Keep the cursor inside the visible region after keyboard navigation
+                if self.scroll_to_cursor && self.data_editing_addr < mem_size {
+                    let line = self.data_editing_addr / cols;
+                    if line < self.scroll_line {
+                        self.scroll_line = line;
+                    } else if line >= self.scroll_line + visible_rows {
+                        self.scroll_line = line + 1 - visible_rows;
+                    }
+                }
+                self.scroll_to_cursor = false;
+
+                self.draw_scrollbar(ui, line_total_count, visible_rows);
+
+                if self.show_stats {
+                    let window_pos = ui.window_pos();
+                    let window_size = ui.window_size();
+                    let strip_end = window_pos[0] + window_size[0] - style.scrollbar_size;
+                    if let Some(addr) = self.stats.draw_entropy_strip(
+                        ui,
+                        [strip_end - ENTROPY_STRIP_WIDTH, window_pos[1]],
+                        [strip_end, window_pos[1] + window_size[1]],
+                        mem_size,
+                    ) {
+                        self.goto_addr = addr;
+                    }
+                }
+
+                if self.data_editing_addr >= mem_size {
                     self.data_editing_addr = usize::MAX;
                 }
                 if self.data_preview_adr >= mem_size {
@@ -324,243 +756,416 @@ impl MemoryEditor {
                                 window_pos[0] + s.pos_ascii_start - s.glyph_width,
                                 window_pos[1] + 9999.0,
                             ],
-                            igGetColorU32Col(StyleColor::Border as i32, 1.0),
+                            colors.separator,
                         )
                         .build();
                 }
+                if self.mid_cols_lines && self.mid_cols_count > 0 {
+                    // Centered in the gap between the last byte of a group and the next
+                    let gap = s.hex_cell_width - s.glyph_width * 2.0 + s.spacing_between_mid_cols;
+                    for n in (self.mid_cols_count..cols).step_by(self.mid_cols_count) {
+                        let x = window_pos[0] + self.hex_pos_x(&s, n) - gap * 0.5;
+                        draw_list
+                            .add_line(
+                                [x, window_pos[1]],
+                                [x, window_pos[1] + 9999.0],
+                                colors.separator,
+                            )
+                            .build();
+                    }
+                }
 
-                let color_text = igGetColorU32Col(StyleColor::Text as i32, 1.0);
-                let color_disabled = igGetColorU32Col(StyleColor::TextDisabled as i32, 1.0);
+                if self.show_header {
+                    let y = ui.cursor_screen_pos()[1];
+                    for n in 0..cols {
+                        draw_list.add_text(
+                            [window_pos[0] + self.hex_pos_x(&s, n), y],
+                            colors.address,
+                            format!("{n:02X}"),
+                        );
+                        if self.show_ascii {
+                            draw_list.add_text(
+                                [
+                                    window_pos[0] + s.pos_ascii_start + s.glyph_width * n as f32,
+                                    y,
+                                ],
+                                colors.address,
+                                format!("{:X}", n % 16),
+                            );
+                        }
+                    }
+                    ui.dummy([0.0, s.header_height]);
+                }
 
-                while clipper.step() {
-                    for line_i in clipper.display_start()..clipper.display_end() {
-                        let mut addr = line_i as usize * self.cols as usize;
-                        ui.text(format!("{:04X}", base_display_addr + addr));
+                let line_end = line_total_count.min(self.scroll_line + visible_rows);
+                for line_i in self.scroll_line..line_end {
+                    let mut addr = line_i * cols;
+                    if self.address_mode == AddressMode::Hidden {
+                        // Still an item for the bytes to be placed on the same line as
+                        ui.dummy([0.0, s.line_height]);
+                    } else {
+                        ui.text_colored(
+                            colors.address,
+                            format!(
+                                "{:0width$X}",
+                                base_display_addr.wrapping_add(addr),
+                                width = s.addr_digit_count
+                            ),
+                        );
+                    }
 
-                        // Draw hexadecimal
-                        let mut n = 0;
-                        while n < self.cols && addr < mem_size {
-                            let mut byte_pos_x = s.pos_hex_start + s.hex_cell_width * n as f32;
-                            if self.mid_cols_count > 0 {
-                                byte_pos_x += (n as usize / self.mid_cols_count) as f32
-                                    * s.spacing_between_mid_cols;
-                            }
-                            ui.same_line_with_pos(byte_pos_x);
-
-                            // Draw Highlight
-                            let is_highlight_from_user_range =
-                                addr >= self.highlight_min && addr < self.highlight_max;
-                            let is_highlight_from_user_func = false; // TODO: use highlight_fn
-                            let is_highlight_from_preview = addr >= self.data_preview_adr
-                                && addr < self.data_preview_adr + preview_data_type_size;
-                            if is_highlight_from_user_range
-                                || is_highlight_from_user_func
-                                || is_highlight_from_preview
-                            {
-                                let pos = ui.cursor_screen_pos();
-                                let mut highlight_width = s.glyph_width * 2.0;
-                                let is_next_byte_highlighted = (addr + 1 < mem_size)
-                                    && (self.highlight_max != usize::MAX
-                                        && addr + 1 < self.highlight_max);
-                                // TODO || (self.HighlightFn && HighlightFn(mem_data, addr + 1)));
-                                if is_next_byte_highlighted || (n + 1 == self.cols) {
-                                    highlight_width = s.hex_cell_width;
-                                    if self.mid_cols_count > 0
-                                        && n > 0
-                                        && (n + 1) < self.cols
-                                        && ((n + 1) % self.mid_cols_count as i32) == 0
-                                    {
-                                        highlight_width += s.spacing_between_mid_cols;
-                                    }
+                    // Draw hexadecimal
+                    let mut n = 0;
+                    while n < self.cols && addr < mem_size {
+                        ui.same_line_with_pos(self.hex_pos_x(&s, n as usize));
+
+                        // Draw Highlight
+                        let is_highlight_from_user_range =
+                            addr >= self.highlight_min && addr < self.highlight_max;
+                        let is_highlight_from_user_func = false; // TODO: use highlight_fn
+                        let is_highlight_from_preview = addr >= self.data_preview_adr
+                            && addr < self.data_preview_adr.saturating_add(preview_data_type_size);
+                        let hovered_ranges =
+                            if self.show_bitmap && !self.bitmap.hovered().is_empty() {
+                                self.bitmap.hovered()
+                            } else {
+                                self.template
+                                    .hovered()
+                                    .filter(|_| self.show_template)
+                                    .map(std::slice::from_ref)
+                                    .unwrap_or_default()
+                            };
+                        let is_highlight_from_hovered =
+                            hovered_ranges.iter().any(|range| range.contains(&addr));
+                        if is_highlight_from_user_range
+                            || is_highlight_from_user_func
+                            || is_highlight_from_preview
+                            || is_highlight_from_hovered
+                        {
+                            let pos = ui.cursor_screen_pos();
+                            let mut highlight_width = s.glyph_width * 2.0;
+                            let is_next_byte_highlighted = (addr + 1 < mem_size)
+                                && ((self.highlight_max != usize::MAX
+                                    && addr + 1 < self.highlight_max)
+                                    || hovered_ranges
+                                        .iter()
+                                        .any(|range| range.contains(&(addr + 1))));
+                            // TODO || (self.HighlightFn && HighlightFn(mem_data, addr + 1)));
+                            if is_next_byte_highlighted || (n + 1 == self.cols) {
+                                highlight_width = s.hex_cell_width;
+                                if self.mid_cols_count > 0
+                                    && n > 0
+                                    && (n + 1) < self.cols
+                                    && ((n + 1) % self.mid_cols_count as i32) == 0
+                                {
+                                    highlight_width += s.spacing_between_mid_cols;
                                 }
-                                draw_list
-                                    .add_rect(
-                                        pos,
-                                        [pos[0] + highlight_width, pos[1] + s.line_height],
-                                        self.highlight_color,
-                                    )
-                                    .filled(true)
-                                    .build();
                             }
+                            draw_list
+                                .add_rect(
+                                    pos,
+                                    [pos[0] + highlight_width, pos[1] + s.line_height],
+                                    colors.highlight,
+                                )
+                                .filled(true)
+                                .build();
+                        }
 
-                            if self.data_editing_addr == addr {
-                                // Display text input on current byte
-                                let mut data_write = false;
-                                // ImGui::PushID((void*)addr);
-                                let _t4 = ui.push_id_usize(addr);
-                                if self.data_editing_take_focus {
-                                    ui.set_keyboard_focus_here_with_offset(
-                                        imgui::FocusedWidget::Offset(0),
-                                    );
-                                    self.addr_input_buf =
-                                        format!("{:04X}", base_display_addr + addr);
-                                    self.data_input_buf = format!("{:02X}", data[addr]);
-                                }
-                                struct UserData {
-                                    current_buf_overwrite: String, // Input
-                                    cursor_pos: i32,               // Output
-                                }
+                        // Flash the last reported hit and underline watched bytes
+                        let pos = ui.cursor_screen_pos();
+                        if let Some(alpha) = self.watchpoints.hit_flash(addr) {
+                            let [r, g, b] = HIT_COLOR;
+                            draw_list
+                                .add_rect(
+                                    pos,
+                                    [pos[0] + s.glyph_width * 2.0, pos[1] + s.line_height],
+                                    [r, g, b, alpha * 0.6],
+                                )
+                                .filled(true)
+                                .build();
+                        }
+                        if let Some(wp) = self.watchpoints.at(addr) {
+                            draw_list
+                                .add_line(
+                                    [pos[0], pos[1] + s.line_height],
+                                    [pos[0] + s.glyph_width * 2.0, pos[1] + s.line_height],
+                                    wp.kind().color(),
+                                )
+                                .build();
+                        }
 
-                                impl InputTextCallbackHandler for &mut UserData {
-                                    fn on_always(&mut self, mut data: imgui::TextCallbackData) {
-                                        if !data.has_selection() {
-                                            self.cursor_pos = data.cursor_pos() as i32;
-                                        }
-                                        if data.selection() == (0..data.str().len()) {
-                                            // When not editing a byte, always refresh its InputText content pulled from underlying memory data
-                                            // (this is a bit tricky, since InputText technically "owns" the master copy of the buffer we edit it in there)
-                                            data.remove_chars(0, data.str().len());
-                                            data.insert_chars(0, &self.current_buf_overwrite);
-                                            *data.selection_start_mut() = 0;
-                                            *data.selection_end_mut() = 2;
-                                            data.set_cursor_pos(0);
-                                        }
+                        // The cursor can rest on bytes that can't be written, only editing is blocked
+                        if self.data_editing_addr == addr
+                            && !self.data_editing_ascii
+                            && self.is_writable(addr)
+                        {
+                            // Display text input on current byte
+                            let mut data_write = false;
+                            // ImGui::PushID((void*)addr);
+                            let _t4 = ui.push_id_usize(addr);
+                            if self.data_editing_take_focus {
+                                ui.set_keyboard_focus_here_with_offset(
+                                    imgui::FocusedWidget::Offset(0),
+                                );
+                                self.addr_input_buf =
+                                    format!("{:04X}", base_display_addr.wrapping_add(addr));
+                                self.data_input_buf = match self.read_byte(mem, addr) {
+                                    Some(byte) => format!("{byte:02X}"),
+                                    None => "??".to_string(),
+                                };
+                            }
+                            struct UserData {
+                                current_buf_overwrite: String, // Input
+                                cursor_pos: i32,               // Output
+                            }
+
+                            impl InputTextCallbackHandler for &mut UserData {
+                                fn on_always(&mut self, mut data: imgui::TextCallbackData) {
+                                    if !data.has_selection() {
+                                        self.cursor_pos = data.cursor_pos() as i32;
+                                    }
+                                    if data.selection() == (0..data.str().len()) {
+                                        // When not editing a byte, always refresh its InputText content pulled from underlying memory data
+                                        // (this is a bit tricky, since InputText technically "owns" the master copy of the buffer we edit it in there)
+                                        data.remove_chars(0, data.str().len());
+                                        data.insert_chars(0, &self.current_buf_overwrite);
+                                        *data.selection_start_mut() = 0;
+                                        *data.selection_end_mut() = 2;
+                                        data.set_cursor_pos(0);
                                     }
                                 }
+                            }
 
-                                let mut user_data = UserData {
-                                    current_buf_overwrite: format!("{:02X}", data[addr]), // TODO: read_fn
-                                    cursor_pos: -1,
-                                };
-                                let flags = InputTextFlags::CHARS_HEXADECIMAL
-                                    | InputTextFlags::ENTER_RETURNS_TRUE
-                                    | InputTextFlags::AUTO_SELECT_ALL
-                                    | InputTextFlags::NO_HORIZONTAL_SCROLL
-                                    | InputTextFlags::CALLBACK_ALWAYS
-                                    | InputTextFlags::ALWAYS_OVERWRITE;
-
-                                ui.set_next_item_width(s.glyph_width * 2.0);
-                                if ui
-                                    .input_text("##data", &mut self.data_input_buf)
-                                    .flags(flags)
-                                    .callback(InputTextCallback::ALWAYS, &mut user_data)
-                                    .build()
-                                {
-                                    data_write = true;
-                                    data_next = true;
-                                } else if !self.data_editing_take_focus && !ui.is_item_active() {
-                                    self.data_editing_addr = usize::MAX;
-                                    data_editing_addr_next = usize::MAX;
-                                }
+                            let mut user_data = UserData {
+                                current_buf_overwrite: match self.read_byte(mem, addr) {
+                                    Some(byte) => format!("{byte:02X}"),
+                                    None => "??".to_string(),
+                                },
+                                cursor_pos: -1,
+                            };
+                            let flags = InputTextFlags::CHARS_HEXADECIMAL
+                                | InputTextFlags::ENTER_RETURNS_TRUE
+                                | InputTextFlags::AUTO_SELECT_ALL
+                                | InputTextFlags::NO_HORIZONTAL_SCROLL
+                                | InputTextFlags::CALLBACK_ALWAYS
+                                | InputTextFlags::ALWAYS_OVERWRITE
+                                | InputTextFlags::ALLOW_TAB_INPUT;
+
+                            let _cursor_colors = (
+                                ui.push_style_color(StyleColor::FrameBg, colors.cursor),
+                                ui.push_style_color(StyleColor::TextSelectedBg, colors.selection),
+                                ui.push_style_color(StyleColor::Text, colors.byte),
+                            );
+                            ui.set_next_item_width(s.glyph_width * 2.0);
+                            if ui
+                                .input_text("##data", &mut self.data_input_buf)
+                                .flags(flags)
+                                .callback(InputTextCallback::ALWAYS, &mut user_data)
+                                .build()
+                            {
+                                data_write = true;
+                                data_next = true;
+                            } else if !self.data_editing_take_focus && !ui.is_item_active() {
+                                self.data_editing_addr = usize::MAX;
+                                data_editing_addr_next = usize::MAX;
+                            }
 
-                                self.data_editing_take_focus = false;
-                                if user_data.cursor_pos >= 2 {
-                                    data_write = true;
-                                    data_next = true;
-                                }
-                                if data_editing_addr_next != usize::MAX {
-                                    data_write = false;
-                                    data_next = false;
-                                }
-                                if data_write {
-                                    if let Ok(val) = u32::from_str_radix(&self.data_input_buf, 16) {
-                                        match self.write_fn {
-                                            Some(_) => todo!(),
-                                            None => data[addr] = val as u8,
-                                        }
-                                    }
+                            self.data_editing_take_focus = false;
+                            if user_data.cursor_pos >= 2 {
+                                data_write = true;
+                                data_next = true;
+                            }
+                            if data_editing_addr_next != usize::MAX {
+                                data_write = false;
+                                data_next = false;
+                            }
+                            if data_write {
+                                if let Ok(val) = u32::from_str_radix(&self.data_input_buf, 16) {
+                                    self.write_byte(mem, addr, val as u8);
                                 }
-                                _t4.pop();
+                            }
+                            _t4.pop();
+                        } else {
+                            let byte = self.read_byte(mem, addr);
+                            let (color_byte, color_zero) = if self.staged.contains_key(&addr) {
+                                (colors.staged, colors.staged)
+                            } else if self.is_protected(addr) {
+                                (colors.protected, colors.protected)
                             } else {
-                                let byte = data[addr]; // TODO: hook into read_fn
+                                (colors.byte, colors.zero)
+                            };
+
+                            if self.data_editing_addr == addr {
+                                // Cursor is in the ascii column or on a byte that can't be
+                                // edited
+                                let pos = ui.cursor_screen_pos();
+                                draw_list
+                                    .add_rect(
+                                        pos,
+                                        [pos[0] + s.glyph_width * 2.0, pos[1] + s.line_height],
+                                        colors.selection,
+                                    )
+                                    .filled(true)
+                                    .build();
+                            }
 
+                            if let Some(byte) = byte {
                                 if self.show_hexii {
                                     if byte >= 32 && byte < 128 {
-                                        ui.text(format!(
-                                            ".{} ",
-                                            char::from_u32_unchecked(byte as u32)
-                                        ));
+                                        ui.text_colored(
+                                            color_byte,
+                                            format!(".{} ", char::from_u32_unchecked(byte as u32)),
+                                        );
                                     } else if byte == 0xFF && self.grey_out_zeros {
-                                        ui.text_disabled("## ");
+                                        ui.text_colored(color_zero, "## ");
                                     } else if byte == 0x00 {
                                         ui.text("   ");
                                     } else {
-                                        ui.text(format!("{byte:02X}"));
+                                        ui.text_colored(color_byte, format!("{byte:02X}"));
                                     }
                                 } else {
                                     if byte == 0 && self.grey_out_zeros {
-                                        ui.text_disabled("00 ");
+                                        ui.text_colored(color_zero, "00 ");
                                     } else {
-                                        ui.text(format!("{byte:02X}"));
+                                        ui.text_colored(color_byte, format!("{byte:02X}"));
                                     }
                                 }
-
-                                if !self.read_only
-                                    && ui.is_item_hovered()
-                                    && ui.is_mouse_clicked(MouseButton::Left)
-                                {
-                                    self.data_editing_take_focus = true;
-                                    data_editing_addr_next = addr;
-                                }
+                            } else {
+                                // Unmapped
+                                ui.text_colored(colors.zero, "?? ");
                             }
 
-                            n += 1;
-                            addr += 1;
+                            if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Left) {
+                                self.data_editing_take_focus = true;
+                                self.data_editing_ascii = false;
+                                data_editing_addr_next = addr;
+                            }
                         }
 
-                        if self.show_ascii {
-                            ui.same_line_with_pos(s.pos_ascii_start);
-                            let mut pos = ui.cursor_screen_pos();
-                            addr = line_i as usize * self.cols as usize;
-                            let t3 = ui.push_id_int(line_i);
-
-                            if ui.invisible_button(
-                                "ascii",
-                                [s.pos_ascii_end - s.pos_ascii_start, s.line_height],
-                            ) {
-                                let x = addr
-                                    + ((ui.io().mouse_pos[0] - pos[0]) / s.glyph_width) as usize;
+                        n += 1;
+                        addr += 1;
+                    }
+
+                    if self.show_ascii {
+                        ui.same_line_with_pos(s.pos_ascii_start);
+                        let mut pos = ui.cursor_screen_pos();
+                        addr = line_i * cols;
+                        let t3 = ui.push_id_usize(line_i);
+
+                        if ui.invisible_button(
+                            "ascii",
+                            [s.pos_ascii_end - s.pos_ascii_start, s.line_height],
+                        ) {
+                            let x =
+                                addr + ((ui.io().mouse_pos[0] - pos[0]) / s.glyph_width) as usize;
+                            if x < mem_size {
                                 self.data_editing_addr = x;
                                 self.data_preview_adr = x;
                                 self.data_editing_take_focus = true;
+                                self.data_editing_ascii = true;
+                            }
+                        }
+                        t3.pop();
+
+                        let mut n = 0;
+                        while n < self.cols && addr < mem_size {
+                            if addr == self.data_editing_addr {
+                                draw_list
+                                    .add_rect(
+                                        pos,
+                                        [pos[0] + s.glyph_width, pos[1] + s.line_height],
+                                        colors.cursor,
+                                    )
+                                    .filled(true)
+                                    .build();
+                                draw_list
+                                    .add_rect(
+                                        pos,
+                                        [pos[0] + s.glyph_width, pos[1] + s.line_height],
+                                        colors.selection,
+                                    )
+                                    .filled(true)
+                                    .build();
                             }
-                            t3.pop();
-
-                            let mut n = 0;
-                            while n < self.cols && addr < mem_size {
-                                if addr == self.data_editing_addr {
-                                    draw_list
-                                        .add_rect(
-                                            pos,
-                                            [pos[0] + s.glyph_width, pos[1] + s.line_height],
-                                            igGetColorU32Col(StyleColor::FrameBg as i32, 1.0),
-                                        )
-                                        .filled(true)
-                                        .build();
-                                    draw_list
-                                        .add_rect(
-                                            pos,
-                                            [pos[0] + s.glyph_width, pos[1] + s.line_height],
-                                            igGetColorU32Col(
-                                                StyleColor::TextSelectedBg as i32,
-                                                1.0,
-                                            ),
-                                        )
-                                        .filled(true)
-                                        .build();
+
+                            let c = self.read_byte(mem, addr);
+                            let disp = match &c {
+                                Some(c) if (32..128).contains(c) => std::slice::from_ref(c),
+                                Some(_) => b".",
+                                None => b"?",
+                            };
+
+                            if self.data_editing_ascii
+                                && addr == self.data_editing_addr
+                                && self.is_writable(addr)
+                            {
+                                // Display text input on current character
+                                let _t5 = ui.push_id_usize(addr);
+                                if self.data_editing_take_focus {
+                                    ui.set_keyboard_focus_here_with_offset(
+                                        imgui::FocusedWidget::Offset(0),
+                                    );
+                                    self.addr_input_buf =
+                                        format!("{:04X}", base_display_addr.wrapping_add(addr));
+                                    self.data_input_buf =
+                                        std::str::from_utf8_unchecked(disp).to_string();
                                 }
 
-                                let c = data[addr]; // TODO: read_fn
-                                let disp = if c < 32 || c >= 128 {
-                                    b"."
-                                } else {
-                                    std::slice::from_raw_parts(&c, 1)
-                                };
+                                let _cursor_colors = (
+                                    ui.push_style_color(StyleColor::FrameBg, colors.cursor),
+                                    ui.push_style_color(
+                                        StyleColor::TextSelectedBg,
+                                        colors.selection,
+                                    ),
+                                    ui.push_style_color(StyleColor::Text, colors.ascii),
+                                );
+                                ui.set_cursor_screen_pos(pos);
+                                ui.set_next_item_width(s.glyph_width);
+                                if ui
+                                    .input_text("##ascii", &mut self.data_input_buf)
+                                    .flags(
+                                        InputTextFlags::AUTO_SELECT_ALL
+                                            | InputTextFlags::NO_HORIZONTAL_SCROLL
+                                            | InputTextFlags::ALWAYS_OVERWRITE
+                                            | InputTextFlags::ALLOW_TAB_INPUT,
+                                    )
+                                    .build()
+                                {
+                                    if let Some(c) =
+                                        self.data_input_buf.bytes().find(|c| (32..128).contains(c))
+                                    {
+                                        self.write_byte(mem, addr, c);
+                                        data_next = true;
+                                    }
+                                } else if !self.data_editing_take_focus && !ui.is_item_active() {
+                                    self.data_editing_addr = usize::MAX;
+                                    data_editing_addr_next = usize::MAX;
+                                }
 
+                                self.data_editing_take_focus = false;
+                                if data_editing_addr_next != usize::MAX {
+                                    data_next = false;
+                                }
+                                _t5.pop();
+                            } else {
                                 draw_list.add_text(
                                     pos,
-                                    if disp[0] == c {
-                                        color_text
+                                    if self.staged.contains_key(&addr) {
+                                        colors.staged
+                                    } else if self.is_protected(addr) {
+                                        colors.protected
+                                    } else if Some(disp[0]) == c {
+                                        colors.ascii
                                     } else {
-                                        color_disabled
+                                        colors.non_printable
                                     },
                                     std::str::from_utf8_unchecked(disp),
                                 );
-
-                                pos[0] += s.glyph_width;
-                                n += 1;
-                                addr += 1;
                             }
+
+                            pos[0] += s.glyph_width;
+                            n += 1;
+                            addr += 1;
                         }
                     }
                 }
@@ -570,24 +1175,184 @@ impl MemoryEditor {
         igSetCursorPosX(s.window_width);
 
         if data_next && self.data_editing_addr + 1 < mem_size {
-            self.data_editing_addr = self.data_editing_addr + 1;
-            self.data_preview_adr = self.data_editing_addr + 1;
+            self.data_editing_addr += 1;
+            self.data_preview_adr = self.data_editing_addr;
             self.data_editing_take_focus = true;
+            self.scroll_to_cursor = true;
         } else if data_editing_addr_next != usize::MAX {
             self.data_editing_addr = data_editing_addr_next;
             self.data_preview_adr = data_editing_addr_next;
             self.data_editing_take_focus = true;
+            self.scroll_to_cursor = true;
         }
 
         let lock_show_data_preview = self.show_data_preview;
         if self.show_options {
             ui.separator();
-            self.draw_options_line(ui, &s, data, mem_size, base_display_addr);
+            self.draw_options_line(ui, &s, mem_size, base_display_addr);
         }
 
         if lock_show_data_preview {
             ui.separator();
-            self.draw_preview_line(ui, &s, data, mem_size);
+            self.draw_preview_line(ui, &s, mem, mem_size);
+        }
+
+        if lock_show_staged {
+            ui.separator();
+            self.draw_staged_line(ui, mem);
+        }
+    }
+
+    fn draw_staged_line<M: MemorySource + ?Sized>(&mut self, ui: &Ui, mem: &mut M) {
+        ui.align_text_to_frame_padding();
+        ui.text_colored(
+            self.style.staged,
+            format!("{} unwritten bytes", self.staged.len()),
+        );
+        ui.same_line();
+        if ui.button("Commit") {
+            self.commit(mem);
+        }
+        ui.same_line();
+        if ui.button("Discard") {
+            self.discard();
+        }
+    }
+
+    /// Draws the scrollbar of the scrolling region and applies dragging it to `scroll_line`.
+    unsafe fn draw_scrollbar(&mut self, ui: &Ui, line_total_count: usize, visible_rows: usize) {
+        let style = ui.style();
+        let max_scroll_line = line_total_count.saturating_sub(visible_rows);
+        self.scroll_line = self.scroll_line.min(max_scroll_line);
+
+        let window_pos = ui.window_pos();
+        let window_size = ui.window_size();
+        let min = [
+            window_pos[0] + window_size[0] - style.scrollbar_size,
+            window_pos[1],
+        ];
+        let max = [
+            window_pos[0] + window_size[0],
+            window_pos[1] + window_size[1],
+        ];
+        let track_height = max[1] - min[1];
+
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(
+                min,
+                max,
+                igGetColorU32Col(StyleColor::ScrollbarBg as i32, 1.0),
+            )
+            .filled(true)
+            .build();
+
+        if max_scroll_line == 0 {
+            return;
+        }
+
+        let grab_height = (track_height * visible_rows as f32 / line_total_count as f32)
+            .max(style.grab_min_size)
+            .min(track_height);
+        let grab_travel = track_height - grab_height;
+        let grab_y =
+            min[1] + (self.scroll_line as f64 / max_scroll_line as f64 * grab_travel as f64) as f32;
+
+        let cursor = ui.cursor_pos();
+        ui.set_cursor_screen_pos(min);
+        ui.invisible_button("##scrollbar", [style.scrollbar_size, track_height]);
+        ui.set_cursor_pos(cursor);
+
+        let mouse_y = ui.io().mouse_pos[1];
+        if ui.is_item_activated() {
+            // Clicking outside of the grab centers it on the mouse
+            self.scrollbar_grab_offset = if mouse_y >= grab_y && mouse_y < grab_y + grab_height {
+                mouse_y - grab_y
+            } else {
+                grab_height * 0.5
+            };
+        }
+
+        let color = if ui.is_item_active() {
+            let t = ((mouse_y - self.scrollbar_grab_offset - min[1]) / grab_travel).clamp(0.0, 1.0);
+            self.scroll_line = (t as f64 * max_scroll_line as f64).round() as usize;
+            StyleColor::ScrollbarGrabActive
+        } else if ui.is_item_hovered() {
+            StyleColor::ScrollbarGrabHovered
+        } else {
+            StyleColor::ScrollbarGrab
+        };
+
+        let grab_y =
+            min[1] + (self.scroll_line as f64 / max_scroll_line as f64 * grab_travel as f64) as f32;
+        draw_list
+            .add_rect(
+                [min[0] + 2.0, grab_y],
+                [max[0] - 2.0, grab_y + grab_height],
+                igGetColorU32Col(color as i32, 1.0),
+            )
+            .filled(true)
+            .rounding(style.scrollbar_rounding)
+            .build();
+    }
+
+    /// Resolves the navigation keys pressed this frame into the next cursor address.
+    fn nav_target(&self, ui: &Ui, mem_size: usize, visible_rows: usize) -> Option<usize> {
+        let addr = self.data_editing_addr;
+        let cols = self.cols as usize;
+        let last = mem_size - 1;
+        let line_start = addr - addr % cols;
+        let page = cols * visible_rows;
+        let ctrl = ui.io().key_ctrl;
+
+        let next = if ui.is_key_pressed(Key::UpArrow) && addr >= cols {
+            addr - cols
+        } else if ui.is_key_pressed(Key::DownArrow)
+            && addr.checked_add(cols).is_some_and(|next| next <= last)
+        {
+            addr + cols
+        } else if ui.is_key_pressed(Key::LeftArrow) && addr > 0 {
+            addr - 1
+        } else if ui.is_key_pressed(Key::RightArrow) && addr < last {
+            addr + 1
+        } else if ui.is_key_pressed(Key::PageUp) {
+            if addr >= page {
+                addr - page
+            } else {
+                addr % cols
+            }
+        } else if ui.is_key_pressed(Key::PageDown) {
+            match addr.checked_add(page).filter(|&next| next <= last) {
+                Some(next) => next,
+                None => {
+                    // Stay in the same column on the last line that has it
+                    let last_line = last - last % cols;
+                    match last_line.checked_add(addr % cols) {
+                        Some(bottom) if bottom <= last => bottom,
+                        _ => last_line - cols + addr % cols,
+                    }
+                }
+            }
+        } else if ui.is_key_pressed(Key::Home) {
+            if ctrl {
+                0
+            } else {
+                line_start
+            }
+        } else if ui.is_key_pressed(Key::End) {
+            if ctrl {
+                last
+            } else {
+                line_start.saturating_add(cols - 1).min(last)
+            }
+        } else {
+            return None;
+        };
+
+        if next != addr {
+            Some(next)
+        } else {
+            None
         }
     }
 
@@ -595,7 +1360,6 @@ impl MemoryEditor {
         &mut self,
         ui: &Ui,
         s: &Sizes,
-        _data: &mut [u8],
         mem_size: usize,
         base_display_addr: usize,
     ) {
@@ -605,24 +1369,65 @@ impl MemoryEditor {
             ui.open_popup("context");
         }
         ui.popup("context", || {
-            ui.set_next_item_width(s.glyph_width * 7.0 + style.frame_padding[0] * 2.0);
-            // TODO: should have speed of 0.2
+            if !self.auto_fit_cols {
+                let mut cols = self.cols;
+                ui.set_next_item_width(s.glyph_width * 7.0 + style.frame_padding[0] * 2.0);
+                // TODO: should have speed of 0.2
+                if ui
+                    .slider_config("##cols", 4, 32)
+                    .display_format("%d cols")
+                    .build(&mut cols)
+                {
+                    self.set_cols(cols.max(1) as usize);
+                }
+                ui.same_line();
+            }
+            if ui.checkbox("Auto-fit", &mut self.auto_fit_cols) {
+                self.contents_width_changed = true;
+            }
+
+            let mut mid_cols = self.mid_cols_count as i32;
+            ui.set_next_item_width(s.glyph_width * 9.0 + style.frame_padding[0] * 2.0);
             if ui
-                .slider_config("##cols", 4, 32)
-                .display_format("%d cols")
-                .build(&mut self.cols)
+                .slider_config("##mid_cols", 0, 16)
+                .display_format("group %d")
+                .build(&mut mid_cols)
             {
+                self.mid_cols_count = mid_cols.max(0) as usize;
                 self.contents_width_changed = true;
-                if self.cols < 1 {
-                    self.cols = 1;
+            }
+            ui.same_line();
+            ui.checkbox("Separator lines", &mut self.mid_cols_lines);
+
+            ui.set_next_item_width(s.glyph_width * 9.0 + style.frame_padding[0] * 2.0);
+            if let Some(_t) = ui.begin_combo("Address", self.address_mode.desc()) {
+                for mode in AddressMode::ALL {
+                    if ui
+                        .selectable_config(mode.desc())
+                        .selected(self.address_mode == mode)
+                        .build()
+                    {
+                        self.address_mode = mode;
+                        self.contents_width_changed = true;
+                    }
                 }
             }
 
+            ui.checkbox("Show Header", &mut self.show_header);
+
             ui.checkbox("Show Data Preview", &mut self.show_data_preview);
             ui.checkbox("Show HexII", &mut self.show_hexii);
             if ui.checkbox("Show Ascii", &mut self.show_ascii) {
                 self.contents_width_changed = true;
             }
+            ui.checkbox("Show Bitmap View", &mut self.show_bitmap);
+            if ui.checkbox("Show Statistics", &mut self.show_stats) {
+                self.contents_width_changed = true;
+            }
+            ui.checkbox("Show Template", &mut self.show_template);
+            ui.checkbox("Show Watches", &mut self.show_watches);
+            ui.checkbox("Show Watchpoints", &mut self.show_watchpoints);
+            ui.checkbox("Confirm writes", &mut self.confirm_writes);
             ui.checkbox("Grey out zeroes", &mut self.grey_out_zeros);
             ui.checkbox("Uppercase Hex", &mut self.uppercase_hex);
         });
@@ -631,7 +1436,7 @@ impl MemoryEditor {
         ui.text(format!(
             "Range {:04X}..{:04X}",
             base_display_addr,
-            base_display_addr + mem_size - 1
+            base_display_addr.saturating_add(mem_size.saturating_sub(1))
         ));
         ui.same_line();
         ui.set_next_item_width(
@@ -642,30 +1447,24 @@ impl MemoryEditor {
             .flags(InputTextFlags::CHARS_HEXADECIMAL | InputTextFlags::ENTER_RETURNS_TRUE)
             .build()
         {
-            if let Ok(x) = usize::from_str_radix(&self.addr_input_buf, 16) {
-                self.goto_addr = x - base_display_addr;
+            if let Some(x) = usize::from_str_radix(&self.addr_input_buf, 16)
+                .ok()
+                .and_then(|x| x.checked_sub(base_display_addr))
+            {
+                self.goto_addr = x;
                 self.highlight_min = usize::MAX;
                 self.highlight_max = usize::MAX;
             }
         }
-
-        if self.goto_addr != usize::MAX {
-            if self.goto_addr < mem_size {
-                ui.child_window("##scrolling").build(|| {
-                    ui.set_scroll_from_pos_y(
-                        ui.cursor_start_pos()[1]
-                            + (self.goto_addr / self.cols as usize) as f32 * ui.text_line_height(),
-                    );
-                });
-                self.data_editing_addr = self.goto_addr;
-                self.data_preview_adr = self.goto_addr;
-                self.data_editing_take_focus = true;
-            }
-            self.goto_addr = usize::MAX;
-        }
     }
 
-    unsafe fn draw_preview_line(&mut self, ui: &Ui, s: &Sizes, data: &mut [u8], mem_size: usize) {
+    unsafe fn draw_preview_line<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        s: &Sizes,
+        mem: &mut M,
+        mem_size: usize,
+    ) {
         let style = ui.style();
 
         ui.align_text_to_frame_padding();
@@ -689,12 +1488,21 @@ impl MemoryEditor {
                 }
             }
         }
-        // TODO: allow changing endianess
-        // ui.same_line();
-        // ui.set_next_item_width(
-        //     (s.GlyphWidth * 6.0) + style.frame_padding[0] * 2.0 + style.item_inner_spacing[0],
-        // );
-        // ui.combo("##combo_endianess", &mut self.PreviewEndianess, )
+        ui.same_line();
+        ui.set_next_item_width(
+            (s.glyph_width * 6.0) + style.frame_padding[0] * 2.0 + style.item_inner_spacing[0],
+        );
+        if let Some(_t) = ui.begin_combo("##combo_endianess", self.preview_endianess.desc()) {
+            for endian in Endianness::ALL {
+                if ui
+                    .selectable_config(endian.desc())
+                    .selected(self.preview_endianess == endian)
+                    .build()
+                {
+                    self.preview_endianess = endian;
+                }
+            }
+        }
 
         let x = s.glyph_width * 6.0;
         let has_value = self.data_preview_adr != usize::MAX;
@@ -703,7 +1511,7 @@ impl MemoryEditor {
         if has_value {
             self.draw_preview_data(
                 self.data_preview_adr,
-                data,
+                mem,
                 mem_size,
                 self.preview_data_type,
                 DataFormat::Dec,
@@ -716,7 +1524,7 @@ impl MemoryEditor {
         if has_value {
             self.draw_preview_data(
                 self.data_preview_adr,
-                data,
+                mem,
                 mem_size,
                 self.preview_data_type,
                 DataFormat::Hex,
@@ -729,7 +1537,7 @@ impl MemoryEditor {
         if has_value {
             self.draw_preview_data(
                 self.data_preview_adr,
-                data,
+                mem,
                 mem_size,
                 self.preview_data_type,
                 DataFormat::Bin,
@@ -741,10 +1549,10 @@ impl MemoryEditor {
         ui.text(if has_value { &buf } else { "N/A" });
     }
 
-    unsafe fn draw_preview_data(
+    unsafe fn draw_preview_data<M: MemorySource + ?Sized>(
         &self,
         addr: usize,
-        data: &mut [u8],
+        mem: &mut M,
         mem_size: usize,
         data_type: DataType,
         data_format: DataFormat,
@@ -758,110 +1566,23 @@ impl MemoryEditor {
             elem_size
         };
 
-        match self.read_fn {
-            Some(_) => todo!(),
-            None => std::ptr::copy(data.as_ptr().add(addr), buf.as_mut_ptr(), size),
-        }
-
         out.clear();
 
-        if data_format == DataFormat::Bin {
-            let mut binbuf = [0; 8];
-            std::ptr::copy(buf.as_ptr(), binbuf.as_mut_ptr(), size);
-            write!(out, "{}", format_binary(&binbuf, size * 8)).unwrap();
+        if !mem.read(addr, &mut buf[..size]) {
+            out.push_str("N/A");
             return;
         }
-
-        use gemstone::mem::MemValue;
-        use std::fmt::Write;
-
-        match data_type {
-            DataType::I8 => match data_format {
-                DataFormat::Dec => write!(out, "{}", i8::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", i8::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::I16 => match data_format {
-                DataFormat::Dec => write!(out, "{}", i16::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", i16::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::I32 => match data_format {
-                DataFormat::Dec => write!(out, "{}", i32::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", i32::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::I64 => match data_format {
-                DataFormat::Dec => write!(out, "{}", i64::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", i64::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::U8 => match data_format {
-                DataFormat::Dec => write!(out, "{}", u8::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", u8::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::U16 => match data_format {
-                DataFormat::Dec => write!(out, "{}", u16::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", u16::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::U32 => match data_format {
-                DataFormat::Dec => write!(out, "{}", u32::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", u32::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::U64 => match data_format {
-                DataFormat::Dec => write!(out, "{}", u64::read_le(buf.as_ptr().cast())).unwrap(),
-                DataFormat::Hex => {
-                    write!(out, "0x{:08X}", u64::read_le(buf.as_ptr().cast())).unwrap()
-                }
-                DataFormat::Bin => unreachable!(),
-            },
-            DataType::F32 => match data_format {
-                DataFormat::Dec => {
-                    write!(out, "{}", f32::from_bits(u32::read_be(buf.as_ptr().cast()))).unwrap()
-                }
-                _ => {}
-            },
-            DataType::F64 => match data_format {
-                DataFormat::Dec => {
-                    write!(out, "{}", f64::from_bits(u64::read_be(buf.as_ptr().cast()))).unwrap()
-                }
-                _ => {}
-            },
+        for (i, byte) in buf[..size].iter_mut().enumerate() {
+            if let Some(&val) = self.staged.get(&(addr + i)) {
+                *byte = val;
+            }
         }
-    }
-}
 
-fn format_binary(buf: &[u8], width: usize) -> String {
-    let mut out_buf = String::new();
-    let n = width / 8;
-
-    for j in (0..=(n - 1)).rev() {
-        for i in 0..8 {
-            out_buf.push(if (buf[j] & (1 << (7 - i))) != 0 {
-                '1'
-            } else {
-                '0'
-            });
-        }
-        out_buf.push(' ');
+        out.push_str(&format_value(
+            &buf,
+            data_type,
+            data_format,
+            self.preview_endianess,
+        ));
     }
-
-    out_buf
 }
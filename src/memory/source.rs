@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 0x100;
+
+/// Memory the editor reads from and writes to.
+///
+/// Only the pages currently on screen are requested, so an implementation can back an
+/// address space far larger than what fits in memory, up to a sparse 64-bit space
+/// reporting a size of `usize::MAX`.
+pub trait MemorySource {
+    /// Size of the address space in bytes.
+    fn size(&self) -> usize;
+
+    /// Fills `buf` with the bytes starting at `addr`.
+    /// Returns `false` if the range is not mapped.
+    fn read(&mut self, addr: usize, buf: &mut [u8]) -> bool;
+
+    /// Writes a single byte at `addr`.
+    fn write(&mut self, addr: usize, val: u8);
+}
+
+impl MemorySource for [u8] {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn read(&mut self, addr: usize, buf: &mut [u8]) -> bool {
+        match addr
+            .checked_add(buf.len())
+            .and_then(|end| self.get(addr..end))
+        {
+            Some(src) => {
+                buf.copy_from_slice(src);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn write(&mut self, addr: usize, val: u8) {
+        if let Some(byte) = self.get_mut(addr) {
+            *byte = val;
+        }
+    }
+}
+
+/// Pages fetched from a [`MemorySource`] during the current frame.
+#[derive(Default)]
+pub(super) struct PageCache {
+    pages: HashMap<usize, Option<Box<[u8]>>>,
+}
+
+impl PageCache {
+    pub(super) fn clear(&mut self) {
+        self.pages.clear();
+    }
+
+    /// The byte at `addr`, `None` if it is unmapped or past the end of `mem`.
+    pub(super) fn get<M: MemorySource + ?Sized>(&mut self, mem: &mut M, addr: usize) -> Option<u8> {
+        if addr >= mem.size() {
+            return None;
+        }
+        let base = addr - addr % PAGE_SIZE;
+        let page = self.pages.entry(base).or_insert_with(|| {
+            let mut buf = vec![0; PAGE_SIZE.min(mem.size() - base)].into_boxed_slice();
+            if mem.read(base, &mut buf) {
+                Some(buf)
+            } else {
+                None
+            }
+        });
+
+        page.as_ref()
+            .and_then(|page| page.get(addr - base).copied())
+    }
+
+    pub(super) fn invalidate(&mut self, addr: usize) {
+        self.pages.remove(&(addr - addr % PAGE_SIZE));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_reads_stop_at_the_end() {
+        let mut mem = [1u8, 2, 3];
+        let mut cache = PageCache::default();
+        assert_eq!(cache.get(&mut mem[..], 2), Some(3));
+        assert_eq!(cache.get(&mut mem[..], 3), None);
+        assert_eq!(cache.get(&mut mem[..], PAGE_SIZE + 1), None);
+        assert_eq!(cache.get(&mut mem[..], usize::MAX), None);
+    }
+}
@@ -0,0 +1,375 @@
+use super::MemorySource;
+use imgui::{Condition, InputTextFlags, MouseButton, ProgressBar, Ui};
+use std::ops::Range;
+
+/// How many bytes are read from the memory source per frame while computing.
+const BYTES_PER_FRAME: usize = 0x10_0000;
+const READ_SIZE: usize = 0x1000;
+const ENTROPY_BLOCKS: usize = 512;
+const MIN_BLOCK_SIZE: usize = 0x100;
+
+pub(super) const ENTROPY_STRIP_WIDTH: f32 = 6.0;
+
+/// Byte frequencies of a region of memory.
+#[derive(Clone)]
+pub struct ByteStats {
+    histogram: [u64; 256],
+    total: u64,
+}
+
+impl Default for ByteStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteStats {
+    pub fn new() -> Self {
+        Self {
+            histogram: [0; 256],
+            total: 0,
+        }
+    }
+
+    pub fn add(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.histogram[byte as usize] += 1;
+        }
+        self.total += bytes.len() as u64;
+    }
+
+    pub fn histogram(&self) -> &[u64; 256] {
+        &self.histogram
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn zeros(&self) -> u64 {
+        self.histogram[0]
+    }
+
+    /// Count of bytes in the printable ascii range.
+    pub fn printable(&self) -> u64 {
+        self.histogram[32..127].iter().sum()
+    }
+
+    /// Shannon entropy in bits per byte, from 0 (a single repeated value) to 8 (uniformly random).
+    pub fn entropy(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        let total = self.total as f64;
+        self.histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
+
+/// Computes [`ByteStats`] over a range a slice at a time, with the entropy of every block.
+pub(super) struct StatsPanel {
+    whole: bool,
+    start_input_buf: String,
+    end_input_buf: String,
+    range: Range<usize>,
+    next: usize,
+    block_size: usize,
+    block: ByteStats,
+    blocks: Vec<f32>,
+    stats: ByteStats,
+    running: bool,
+}
+
+impl StatsPanel {
+    pub(super) fn new() -> Self {
+        Self {
+            whole: true,
+            start_input_buf: String::with_capacity(32),
+            end_input_buf: String::with_capacity(32),
+            range: 0..0,
+            next: 0,
+            block_size: MIN_BLOCK_SIZE,
+            block: ByteStats::new(),
+            blocks: Vec::new(),
+            stats: ByteStats::new(),
+            running: false,
+        }
+    }
+
+    fn set_range_input(&mut self, range: &Range<usize>) {
+        self.start_input_buf = format!("{:X}", range.start);
+        self.end_input_buf = format!("{:X}", range.end);
+    }
+
+    fn start(&mut self, range: Range<usize>) {
+        let len = range.end.saturating_sub(range.start);
+        self.block_size = (len / ENTROPY_BLOCKS + 1).max(MIN_BLOCK_SIZE);
+        self.next = range.start;
+        self.range = range;
+        self.block = ByteStats::new();
+        self.blocks.clear();
+        self.stats = ByteStats::new();
+        self.running = true;
+    }
+
+    fn step<M: MemorySource + ?Sized>(&mut self, mem: &mut M) {
+        let mut buf = [0; READ_SIZE];
+        let mut budget = BYTES_PER_FRAME;
+
+        while self.running && budget > 0 {
+            let block_start = self.range.start
+                + (self.next - self.range.start) / self.block_size * self.block_size;
+            let block_end = block_start
+                .saturating_add(self.block_size)
+                .min(self.range.end);
+            let len = (block_end - self.next).min(READ_SIZE).min(budget);
+
+            // Unmapped memory is skipped and doesn't count towards the statistics
+            if mem.read(self.next, &mut buf[..len]) {
+                self.block.add(&buf[..len]);
+                self.stats.add(&buf[..len]);
+            }
+            self.next += len;
+            budget -= len;
+
+            if self.next == block_end {
+                self.blocks.push(self.block.entropy() as f32);
+                self.block = ByteStats::new();
+            }
+            if self.next >= self.range.end {
+                self.running = false;
+            }
+        }
+    }
+
+    /// Draws the panel, the range starts out as `selection`, the editor's selected bytes.
+    pub(super) fn draw_window<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        mem: &mut M,
+        opened: &mut bool,
+        selection: Option<Range<usize>>,
+    ) {
+        if self.running {
+            self.step(mem);
+        }
+
+        ui.window("Byte Statistics")
+            .size([320.0, 360.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                if ui.radio_button_bool("Whole memory", self.whole) {
+                    self.whole = true;
+                }
+                ui.same_line();
+                if ui.radio_button_bool("Range", !self.whole) && self.whole {
+                    self.whole = false;
+                    if let Some(selection) = &selection {
+                        self.set_range_input(selection);
+                    }
+                }
+                if let Some(selection) = selection.as_ref().filter(|_| !self.whole) {
+                    ui.same_line();
+                    if ui.button("Use selection") {
+                        self.set_range_input(selection);
+                    }
+                }
+
+                if !self.whole {
+                    let width = ui.calc_text_size("0000000000000000")[0];
+                    let flags = InputTextFlags::CHARS_HEXADECIMAL;
+                    ui.set_next_item_width(width);
+                    ui.input_text("Start", &mut self.start_input_buf)
+                        .flags(flags)
+                        .build();
+                    ui.set_next_item_width(width);
+                    ui.input_text("End", &mut self.end_input_buf)
+                        .flags(flags)
+                        .build();
+                }
+
+                if ui.button("Compute") {
+                    let range = if self.whole {
+                        0..mem.size()
+                    } else {
+                        let start = usize::from_str_radix(&self.start_input_buf, 16).unwrap_or(0);
+                        let end = usize::from_str_radix(&self.end_input_buf, 16)
+                            .unwrap_or(usize::MAX)
+                            .min(mem.size());
+                        start.min(end)..end
+                    };
+                    self.start(range);
+                }
+
+                if self.running {
+                    ui.same_line();
+                    if ui.button("Stop") {
+                        self.running = false;
+                    }
+                    let len = (self.range.end - self.range.start).max(1);
+                    let done = self.next - self.range.start;
+                    ProgressBar::new((done as f64 / len as f64) as f32).build(ui);
+                }
+
+                ui.separator();
+
+                let total = self.stats.total();
+                let percent = |count: u64| {
+                    if total > 0 {
+                        count as f64 * 100.0 / total as f64
+                    } else {
+                        0.0
+                    }
+                };
+                ui.text(format!("Bytes      {total}"));
+                ui.text(format!(
+                    "Zero       {} ({:.1}%)",
+                    self.stats.zeros(),
+                    percent(self.stats.zeros())
+                ));
+                ui.text(format!(
+                    "Printable  {} ({:.1}%)",
+                    self.stats.printable(),
+                    percent(self.stats.printable())
+                ));
+                ui.text(format!("Entropy    {:.3} bits/byte", self.stats.entropy()));
+
+                let histogram: Vec<f32> = self
+                    .stats
+                    .histogram()
+                    .iter()
+                    .map(|&count| count as f32)
+                    .collect();
+                ui.plot_histogram("##histogram", &histogram)
+                    .scale_min(0.0)
+                    .graph_size([ui.content_region_avail()[0], 100.0])
+                    .build();
+                if ui.is_item_hovered() {
+                    let min = ui.item_rect_min();
+                    let width = ui.item_rect_size()[0];
+                    let byte =
+                        (((ui.io().mouse_pos[0] - min[0]) / width * 256.0) as usize).min(255);
+                    ui.tooltip_text(format!(
+                        "{byte:02X}: {} ({:.2}%)",
+                        self.stats.histogram()[byte],
+                        percent(self.stats.histogram()[byte])
+                    ));
+                }
+            });
+    }
+
+    /// Draws the entropy of every computed block as a strip, laid out over the whole address
+    /// space like the scrollbar next to it. Returns the start of a block that was clicked.
+    pub(super) fn draw_entropy_strip(
+        &self,
+        ui: &Ui,
+        min: [f32; 2],
+        max: [f32; 2],
+        mem_size: usize,
+    ) -> Option<usize> {
+        if self.blocks.is_empty() || mem_size == 0 {
+            return None;
+        }
+
+        let draw_list = ui.get_window_draw_list();
+        let height = max[1] - min[1];
+        let addr_y = |addr: usize| min[1] + (addr as f64 / mem_size as f64 * height as f64) as f32;
+        let mouse = ui.io().mouse_pos;
+        let mut hovered = None;
+
+        for (i, &entropy) in self.blocks.iter().enumerate() {
+            let start = self.range.start + i * self.block_size;
+            let y0 = addr_y(start);
+            let y1 = addr_y(start.saturating_add(self.block_size)).max(y0 + 1.0);
+            let t = entropy / 8.0;
+            draw_list
+                .add_rect(
+                    [min[0], y0],
+                    [max[0], y1],
+                    [t, 1.0 - (t * 2.0 - 1.0).abs(), 1.0 - t, 1.0],
+                )
+                .filled(true)
+                .build();
+
+            if mouse[0] >= min[0] && mouse[0] < max[0] && mouse[1] >= y0 && mouse[1] < y1 {
+                hovered = Some((start, entropy));
+            }
+        }
+
+        let (start, entropy) = hovered?;
+        if !ui.is_window_hovered() {
+            return None;
+        }
+        ui.tooltip_text(format!("{start:X}: {entropy:.2} bits/byte"));
+        if ui.is_mouse_clicked(MouseButton::Left) {
+            Some(start)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes from a small linear congruential generator.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 1u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entropy_limits() {
+        let mut uniform = ByteStats::new();
+        uniform.add(&(0..=255).collect::<Vec<u8>>());
+        assert_eq!(uniform.entropy(), 8.0);
+
+        let mut constant = ByteStats::new();
+        constant.add(&[0x42; 1000]);
+        assert_eq!(constant.entropy(), 0.0);
+
+        assert_eq!(ByteStats::new().entropy(), 0.0);
+    }
+
+    #[test]
+    fn steps_match_a_single_pass() {
+        let mut data = noise(BYTES_PER_FRAME * 3 + 123);
+        let mut panel = StatsPanel::new();
+        panel.start(0..data.len());
+
+        let mut frames = 0;
+        while panel.running {
+            panel.step(data.as_mut_slice());
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+
+        let mut whole = ByteStats::new();
+        whole.add(&data);
+        assert_eq!(panel.stats.histogram(), whole.histogram());
+        assert_eq!(panel.stats.entropy(), whole.entropy());
+
+        let blocks: Vec<f32> = data
+            .chunks(panel.block_size)
+            .map(|chunk| {
+                let mut block = ByteStats::new();
+                block.add(chunk);
+                block.entropy() as f32
+            })
+            .collect();
+        assert_eq!(panel.blocks, blocks);
+    }
+}
@@ -0,0 +1,79 @@
+use imgui::{FontId, StyleColor, Ui};
+
+/// Colors and font of the memory editor.
+///
+/// Colors left to `None` follow the imgui style, so the editor matches the rest of the
+/// application unless told otherwise.
+#[derive(Clone)]
+pub struct MemoryEditorStyle {
+    pub address: Option<[f32; 4]>,
+    pub byte: Option<[f32; 4]>,
+    /// Zeroes when "Grey out zeroes" is enabled, and unmapped bytes
+    pub zero: Option<[f32; 4]>,
+    pub ascii: Option<[f32; 4]>,
+    pub non_printable: Option<[f32; 4]>,
+    /// Background of the byte under the cursor
+    pub cursor: Option<[f32; 4]>,
+    pub selection: Option<[f32; 4]>,
+    pub separator: Option<[f32; 4]>,
+    pub highlight: [f32; 4],
+    /// Staged bytes waiting to be committed
+    pub staged: [f32; 4],
+    /// Bytes refused by [`MemoryEditor::set_writable_fn`](super::MemoryEditor::set_writable_fn)
+    pub protected: [f32; 4],
+    /// A monospace font, the layout assumes every glyph has the same width.
+    pub font: Option<FontId>,
+}
+
+impl Default for MemoryEditorStyle {
+    fn default() -> Self {
+        Self {
+            address: None,
+            byte: None,
+            zero: None,
+            ascii: None,
+            non_printable: None,
+            cursor: None,
+            selection: None,
+            separator: None,
+            highlight: [1.0, 1.0, 1.0, 0.2],
+            staged: [1.0, 0.8, 0.2, 1.0],
+            protected: [0.8, 0.45, 0.45, 1.0],
+            font: None,
+        }
+    }
+}
+
+/// The style with the imgui colors filled in for the current frame.
+pub(super) struct Colors {
+    pub(super) address: [f32; 4],
+    pub(super) byte: [f32; 4],
+    pub(super) zero: [f32; 4],
+    pub(super) ascii: [f32; 4],
+    pub(super) non_printable: [f32; 4],
+    pub(super) cursor: [f32; 4],
+    pub(super) selection: [f32; 4],
+    pub(super) separator: [f32; 4],
+    pub(super) highlight: [f32; 4],
+    pub(super) staged: [f32; 4],
+    pub(super) protected: [f32; 4],
+}
+
+impl MemoryEditorStyle {
+    pub(super) fn colors(&self, ui: &Ui) -> Colors {
+        let color = |color: Option<[f32; 4]>, fallback| color.unwrap_or(ui.style_color(fallback));
+        Colors {
+            address: color(self.address, StyleColor::Text),
+            byte: color(self.byte, StyleColor::Text),
+            zero: color(self.zero, StyleColor::TextDisabled),
+            ascii: color(self.ascii, StyleColor::Text),
+            non_printable: color(self.non_printable, StyleColor::TextDisabled),
+            cursor: color(self.cursor, StyleColor::FrameBg),
+            selection: color(self.selection, StyleColor::TextSelectedBg),
+            separator: color(self.separator, StyleColor::Border),
+            highlight: self.highlight,
+            staged: self.staged,
+            protected: self.protected,
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use super::{
+    parser::{BinOp, Expr, Field, Prim, Stmt, Template},
+    TemplateError,
+};
+use crate::memory::{value::to_raw, DataType, Endianness, MemorySource};
+use std::{fmt, ops::Range};
+
+/// Limits keeping a runaway length or a recursive struct from hanging the editor.
+const MAX_NODES: usize = 100_000;
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Structs and arrays, their values are their children
+    None,
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Char(u8),
+    Enum(i128, Option<String>),
+    /// A `char` array, up to the first NUL
+    Text(String),
+}
+
+impl Value {
+    fn as_int(&self) -> Option<i128> {
+        match self {
+            Value::Int(val) | Value::Enum(val, _) => Some(*val),
+            Value::Float(val) => Some(*val as i128),
+            Value::Bool(val) => Some(*val as i128),
+            Value::Char(val) => Some(*val as i128),
+            Value::None | Value::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::None => Ok(()),
+            Value::Int(val) if *val < 0 => write!(f, "{val}"),
+            Value::Int(val) => write!(f, "{val} (0x{val:X})"),
+            Value::Float(val) => write!(f, "{val}"),
+            Value::Bool(val) => write!(f, "{val}"),
+            Value::Char(val) if val.is_ascii_graphic() || *val == b' ' => {
+                write!(f, "'{}'", *val as char)
+            }
+            Value::Char(val) => write!(f, "'\\x{val:02X}'"),
+            Value::Enum(val, Some(name)) => write!(f, "{name} ({val})"),
+            Value::Enum(val, None) => write!(f, "{val}"),
+            Value::Text(text) => write!(f, "{text:?}"),
+        }
+    }
+}
+
+/// A field of an evaluated template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub type_name: String,
+    pub range: Range<usize>,
+    pub value: Value,
+    pub children: Vec<Node>,
+}
+
+pub(super) struct Evaluator<'a, M: MemorySource + ?Sized> {
+    tpl: &'a Template,
+    mem: &'a mut M,
+    /// Fields evaluated so far in every struct being evaluated, the placements at the bottom
+    scopes: Vec<Vec<Node>>,
+    pos: usize,
+    nodes: usize,
+}
+
+impl<'a, M: MemorySource + ?Sized> Evaluator<'a, M> {
+    pub(super) fn new(tpl: &'a Template, mem: &'a mut M) -> Self {
+        Self {
+            tpl,
+            mem,
+            scopes: vec![Vec::new()],
+            pos: 0,
+            nodes: 0,
+        }
+    }
+
+    pub(super) fn run(mut self) -> Result<Vec<Node>, TemplateError> {
+        for placement in &self.tpl.placements {
+            let line = placement.field.line;
+            let addr = self
+                .expr(&placement.addr)
+                .map_err(|msg| TemplateError::new(line, msg))?;
+            self.pos = usize::try_from(addr)
+                .map_err(|_| TemplateError::new(line, format!("invalid address {addr}")))?;
+            let node = self.field(&placement.field, 0)?;
+            self.scopes[0].push(node);
+        }
+
+        Ok(self.scopes.pop().unwrap())
+    }
+
+    fn field(&mut self, field: &Field, depth: usize) -> Result<Node, TemplateError> {
+        let len = match &field.len {
+            Some(len) => len,
+            None => return self.value(field, field.name.clone(), depth),
+        };
+
+        let err = |msg| TemplateError::new(field.line, msg);
+        let len = self.expr(len).map_err(err)?;
+        let len = match usize::try_from(len) {
+            Ok(len) if len <= MAX_NODES => len,
+            _ => return Err(err(format!("array length {len} is out of range"))),
+        };
+
+        let start = self.pos;
+        let type_name = format!("{}[{len}]", field.type_name);
+
+        if field.type_name == "char" {
+            let bytes = self.read(len, field.line)?;
+            let text = bytes.split(|&b| b == 0).next().unwrap_or(&[]);
+            return Ok(Node {
+                name: field.name.clone(),
+                type_name,
+                range: start..self.pos,
+                value: Value::Text(String::from_utf8_lossy(text).into_owned()),
+                children: Vec::new(),
+            });
+        }
+
+        let mut children = Vec::with_capacity(len);
+        for i in 0..len {
+            children.push(self.value(field, format!("[{i}]"), depth)?);
+        }
+
+        Ok(Node {
+            name: field.name.clone(),
+            type_name,
+            range: start..self.pos,
+            value: Value::None,
+            children,
+        })
+    }
+
+    /// Evaluates a single value of the field's type.
+    fn value(&mut self, field: &Field, name: String, depth: usize) -> Result<Node, TemplateError> {
+        let line = field.line;
+        let type_name = &field.type_name;
+        let endian = field.endian.unwrap_or(self.tpl.endian);
+        let start = self.pos;
+
+        self.nodes += 1;
+        if self.nodes > MAX_NODES {
+            return Err(TemplateError::new(line, "too many fields"));
+        }
+
+        let mut children = Vec::new();
+        let value = if let Some(prim) = Prim::from_name(type_name) {
+            let bytes = self.read(prim.size(), line)?;
+            match prim {
+                Prim::Data(DataType::F32) => {
+                    let raw = to_raw(&bytes, DataType::F32, endian);
+                    Value::Float(f32::from_bits(raw as u32) as f64)
+                }
+                Prim::Data(DataType::F64) => {
+                    Value::Float(f64::from_bits(to_raw(&bytes, DataType::F64, endian)))
+                }
+                Prim::Data(typ) => Value::Int(decode_int(&bytes, typ, endian)),
+                Prim::Char => Value::Char(bytes[0]),
+                Prim::Bool => Value::Bool(bytes[0] != 0),
+            }
+        } else if let Some(decl) = self.tpl.enums.get(type_name) {
+            let bytes = self.read(decl.base.size(), line)?;
+            let val = decode_int(&bytes, decl.base, endian);
+            let variant = decl.variants.iter().find(|(_, v)| *v == val);
+            Value::Enum(val, variant.map(|(name, _)| name.clone()))
+        } else if let Some(decl) = self.tpl.bitfields.get(type_name) {
+            let bytes = self.read(decl.base.size(), line)?;
+            let raw = to_raw(&bytes, decl.base, endian);
+            let mut shift = 0;
+            for (name, bits) in &decl.fields {
+                let val = (raw >> shift) & (u64::MAX >> (64 - bits));
+                shift += bits;
+                if name != "_" {
+                    children.push(Node {
+                        name: name.clone(),
+                        type_name: format!("{bits} bits"),
+                        range: start..self.pos,
+                        value: Value::Int(val as i128),
+                        children: Vec::new(),
+                    });
+                }
+            }
+            Value::None
+        } else if let Some(body) = self.tpl.structs.get(type_name) {
+            if depth >= MAX_DEPTH {
+                return Err(TemplateError::new(line, "structs are nested too deep"));
+            }
+            self.scopes.push(Vec::new());
+            for stmt in body {
+                self.stmt(stmt, depth + 1)?;
+            }
+            children = self.scopes.pop().unwrap();
+            Value::None
+        } else {
+            return Err(TemplateError::new(
+                line,
+                format!("unknown type `{type_name}`"),
+            ));
+        };
+
+        Ok(Node {
+            name,
+            type_name: type_name.clone(),
+            range: start..self.pos,
+            value,
+            children,
+        })
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, depth: usize) -> Result<(), TemplateError> {
+        match stmt {
+            Stmt::Field(field) => {
+                let node = self.field(field, depth)?;
+                self.scopes.last_mut().unwrap().push(node);
+            }
+            Stmt::If {
+                branches,
+                otherwise,
+                line,
+            } => {
+                let mut body = otherwise;
+                for (cond, branch) in branches {
+                    let cond = self
+                        .expr(cond)
+                        .map_err(|msg| TemplateError::new(*line, msg))?;
+                    if cond != 0 {
+                        body = branch;
+                        break;
+                    }
+                }
+                for stmt in body {
+                    self.stmt(stmt, depth)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, len: usize, line: usize) -> Result<Vec<u8>, TemplateError> {
+        let mut buf = vec![0; len];
+        let end = self.pos.checked_add(len);
+        let mapped = matches!(end, Some(end) if end <= self.mem.size());
+        if !mapped || !self.mem.read(self.pos, &mut buf) {
+            return Err(TemplateError::new(
+                line,
+                format!("address {:X} is not mapped", self.pos),
+            ));
+        }
+        self.pos += len;
+        Ok(buf)
+    }
+
+    fn expr(&self, expr: &Expr) -> Result<i128, String> {
+        match expr {
+            Expr::Int(val) => Ok(*val),
+            Expr::Dollar => Ok(self.pos as i128),
+            Expr::EnumConst(typ, name) => self
+                .tpl
+                .enums
+                .get(typ)
+                .and_then(|decl| decl.variants.iter().find(|(n, _)| n == name))
+                .map(|(_, val)| *val)
+                .ok_or_else(|| format!("unknown enum value `{typ}::{name}`")),
+            Expr::Ident(_) | Expr::Member(..) | Expr::Index(..) => {
+                let node = self.lookup(expr)?;
+                node.value
+                    .as_int()
+                    .ok_or_else(|| format!("`{}` is not a number", node.name))
+            }
+            Expr::Unary(op, val) => Ok(op.apply(self.expr(val)?)),
+            Expr::Binary(BinOp::And, a, b) => {
+                Ok((self.expr(a)? != 0 && self.expr(b)? != 0) as i128)
+            }
+            Expr::Binary(BinOp::Or, a, b) => Ok((self.expr(a)? != 0 || self.expr(b)? != 0) as i128),
+            Expr::Binary(op, a, b) => op
+                .apply(self.expr(a)?, self.expr(b)?)
+                .ok_or_else(|| "division by zero".to_owned()),
+        }
+    }
+
+    /// Finds the field an expression refers to, innermost struct first.
+    fn lookup(&self, expr: &Expr) -> Result<&Node, String> {
+        match expr {
+            Expr::Ident(name) => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.iter().rev().find(|node| node.name == *name))
+                .ok_or_else(|| format!("unknown field `{name}`")),
+            Expr::Member(base, name) => {
+                let base = self.lookup(base)?;
+                base.children
+                    .iter()
+                    .find(|node| node.name == *name)
+                    .ok_or_else(|| format!("`{}` has no field `{name}`", base.name))
+            }
+            Expr::Index(base, idx) => {
+                let base = self.lookup(base)?;
+                let idx = self.expr(idx)?;
+                usize::try_from(idx)
+                    .ok()
+                    .and_then(|idx| base.children.get(idx))
+                    .ok_or_else(|| format!("index {idx} is out of bounds of `{}`", base.name))
+            }
+            _ => Err("expected a field".to_owned()),
+        }
+    }
+}
+
+fn decode_int(bytes: &[u8], typ: DataType, endian: Endianness) -> i128 {
+    let raw = to_raw(bytes, typ, endian);
+    match typ {
+        DataType::I8 => raw as i8 as i128,
+        DataType::I16 => raw as i16 as i128,
+        DataType::I32 => raw as i32 as i128,
+        DataType::I64 => raw as i64 as i128,
+        _ => raw as i128,
+    }
+}
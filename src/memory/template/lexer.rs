@@ -0,0 +1,88 @@
+use super::TemplateError;
+
+/// Punctuation, longest first so `<<` isn't lexed as two `<`.
+const PUNCTS: [&str; 33] = [
+    "::", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "{", "}", "(", ")", "[", "]", ";", ":",
+    ",", ".", "@", "=", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~", "<", ">",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Token {
+    Ident(String),
+    Int(u64),
+    Punct(&'static str),
+    /// `$`, the address of the next field
+    Dollar,
+    Eof,
+}
+
+pub(super) fn lex(src: &str) -> Result<Vec<(Token, usize)>, TemplateError> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let rest = &src[i..];
+
+        if c == b'\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_ascii_whitespace() {
+            i += 1;
+        } else if rest.starts_with("//") {
+            i += rest.find('\n').unwrap_or(rest.len());
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .ok_or_else(|| TemplateError::new(line, "unterminated comment"))?;
+            line += rest[..end + 4].matches('\n').count();
+            i += end + 4;
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((Token::Ident(rest[..len].to_owned()), line));
+            i += len;
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let text = rest[..len].replace('_', "");
+            let val = if let Some(hex) = text.strip_prefix("0x") {
+                u64::from_str_radix(hex, 16)
+            } else if let Some(bin) = text.strip_prefix("0b") {
+                u64::from_str_radix(bin, 2)
+            } else {
+                text.parse()
+            }
+            .map_err(|_| TemplateError::new(line, format!("invalid number `{}`", &rest[..len])))?;
+            tokens.push((Token::Int(val), line));
+            i += len;
+        } else if c == b'\'' {
+            match bytes.get(i + 1..i + 3) {
+                Some([ch, b'\'']) if ch.is_ascii() => {
+                    tokens.push((Token::Int(*ch as u64), line));
+                    i += 3;
+                }
+                _ => return Err(TemplateError::new(line, "invalid character literal")),
+            }
+        } else if c == b'$' {
+            tokens.push((Token::Dollar, line));
+            i += 1;
+        } else if let Some(punct) = PUNCTS.iter().find(|p| rest.starts_with(**p)) {
+            tokens.push((Token::Punct(punct), line));
+            i += punct.len();
+        } else {
+            let ch = rest.chars().next().unwrap();
+            return Err(TemplateError::new(
+                line,
+                format!("unexpected character `{ch}`"),
+            ));
+        }
+    }
+
+    tokens.push((Token::Eof, line));
+    Ok(tokens)
+}
@@ -0,0 +1,342 @@
+use super::MemorySource;
+use imgui::{Condition, MouseButton, TableFlags, Ui};
+use std::{error::Error, fmt, ops::Range};
+
+mod eval;
+mod lexer;
+mod parser;
+
+pub use eval::{Node, Value};
+pub use parser::Template;
+
+/// Error from parsing or evaluating a template, with the line it happened on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl TemplateError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for TemplateError {}
+
+impl Template {
+    /// Parses a template. Types are declared C-style and placed at an address with `@`:
+    ///
+    /// ```text
+    /// endian little;
+    ///
+    /// enum Kind : u8 { Empty, Data = 4 };
+    /// bitfield Flags : u16 { ready : 1; mode : 3; _ : 12; };
+    ///
+    /// struct Entry {
+    ///     Kind kind;
+    ///     Flags flags;
+    ///     if (kind == Kind::Data) {
+    ///         be u32 len;
+    ///         u8 data[len];
+    ///     }
+    /// };
+    ///
+    /// struct Header {
+    ///     char magic[4];
+    ///     u16 count;
+    ///     Entry entries[count];
+    /// };
+    ///
+    /// Header header @ 0x100;
+    /// ```
+    ///
+    /// Fields are `[le|be] type name[len];` where `type` is an integer, float, `char`, `bool`
+    /// or a declared type. Expressions can refer to earlier fields, `header.entries[0].len`,
+    /// enum values and `$`, the address of the next field.
+    pub fn parse(src: &str) -> Result<Self, TemplateError> {
+        parser::Parser::new(src)?.template()
+    }
+
+    /// Reads the placed fields from memory.
+    pub fn eval<M: MemorySource + ?Sized>(&self, mem: &mut M) -> Result<Vec<Node>, TemplateError> {
+        eval::Evaluator::new(self, mem).run()
+    }
+}
+
+/// Shows a template evaluated against memory as a tree.
+pub struct TemplateView {
+    source: String,
+    template: Option<Template>,
+    error: Option<TemplateError>,
+    nodes: Vec<Node>,
+    hovered: Option<Range<usize>>,
+}
+
+impl Default for TemplateView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemplateView {
+    pub fn new() -> Self {
+        Self {
+            source: String::new(),
+            template: None,
+            error: None,
+            nodes: Vec::new(),
+            hovered: None,
+        }
+    }
+
+    /// Replaces the template, it is evaluated every frame while the window is open.
+    pub fn set_source(&mut self, src: &str) -> Result<(), TemplateError> {
+        self.source = src.to_owned();
+        self.apply()
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Fields from the last evaluation.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    fn apply(&mut self) -> Result<(), TemplateError> {
+        self.nodes.clear();
+        match Template::parse(&self.source) {
+            Ok(template) => {
+                self.template = Some(template);
+                self.error = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.template = None;
+                self.error = Some(err.clone());
+                Err(err)
+            }
+        }
+    }
+
+    pub(super) fn hovered(&self) -> Option<&Range<usize>> {
+        self.hovered.as_ref()
+    }
+
+    /// Draws the template window. Returns the address of a field that was clicked.
+    pub(super) fn draw_window<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        mem: &mut M,
+        opened: &mut bool,
+    ) -> Option<usize> {
+        let mut clicked = None;
+        self.hovered = None;
+
+        if let Some(template) = &self.template {
+            match template.eval(mem) {
+                Ok(nodes) => {
+                    self.nodes = nodes;
+                    self.error = None;
+                }
+                Err(err) => self.error = Some(err),
+            }
+        }
+
+        ui.window("Template")
+            .size([480.0, 400.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                if ui.collapsing_header("Source", imgui::TreeNodeFlags::empty()) {
+                    ui.input_text_multiline(
+                        "##source",
+                        &mut self.source,
+                        [-1.0, ui.text_line_height() * 12.0],
+                    )
+                    .allow_tab_input(true)
+                    .build();
+                    if ui.button("Apply") {
+                        // The error is shown below
+                        let _ = self.apply();
+                    }
+                }
+
+                if let Some(err) = &self.error {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], err.to_string());
+                }
+
+                let flags = TableFlags::BORDERS_INNER_V
+                    | TableFlags::ROW_BG
+                    | TableFlags::RESIZABLE
+                    | TableFlags::SCROLL_Y;
+                if let Some(_t) = ui.begin_table_with_flags("##nodes", 4, flags) {
+                    ui.table_setup_scroll_freeze(0, 1);
+                    ui.table_setup_column("Name");
+                    ui.table_setup_column("Value");
+                    ui.table_setup_column("Type");
+                    ui.table_setup_column("Range");
+                    ui.table_headers_row();
+
+                    for (idx, node) in self.nodes.iter().enumerate() {
+                        draw_node(ui, node, idx, &mut self.hovered, &mut clicked);
+                    }
+                }
+            });
+
+        clicked
+    }
+}
+
+fn draw_node(
+    ui: &Ui,
+    node: &Node,
+    idx: usize,
+    hovered: &mut Option<Range<usize>>,
+    clicked: &mut Option<usize>,
+) {
+    let _id = ui.push_id_usize(idx);
+
+    ui.table_next_row();
+    ui.table_next_column();
+    let open = ui
+        .tree_node_config(&node.name)
+        .leaf(node.children.is_empty())
+        .open_on_arrow(true)
+        .push();
+    if ui.is_item_hovered() {
+        *hovered = Some(node.range.clone());
+        if ui.is_mouse_double_clicked(MouseButton::Left) {
+            *clicked = Some(node.range.start);
+        }
+    }
+
+    ui.table_next_column();
+    ui.text(node.value.to_string());
+    ui.table_next_column();
+    ui.text(&node.type_name);
+    ui.table_next_column();
+    ui.text(format!("{:X}..{:X}", node.range.start, node.range.end));
+
+    if let Some(_t) = open {
+        for (idx, child) in node.children.iter().enumerate() {
+            draw_node(ui, child, idx, hovered, clicked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, mem: &mut [u8]) -> Result<Vec<Node>, TemplateError> {
+        Template::parse(src)?.eval(mem)
+    }
+
+    #[test]
+    fn primitives_and_endianness() {
+        let mut mem = [0x34, 0x12, 0x12, 0x34, 0xFF, 0x00, 0x00, 0x80, 0x3F];
+        let nodes = eval(
+            "struct S { u16 a; be u16 b; i8 c; f32 d; };\nS s @ 0;",
+            &mut mem,
+        )
+        .unwrap();
+
+        let s = &nodes[0];
+        assert_eq!(s.range, 0..9);
+        assert_eq!(s.children[0].value, Value::Int(0x1234));
+        assert_eq!(s.children[1].value, Value::Int(0x1234));
+        assert_eq!(s.children[2].value, Value::Int(-1));
+        assert_eq!(s.children[3].value, Value::Float(1.0));
+        assert_eq!(s.children[3].range, 5..9);
+    }
+
+    #[test]
+    fn arrays_with_computed_lengths() {
+        let mut mem = [3, 1, 2, 3, b'h', b'i', 0, 0];
+        let nodes = eval(
+            "struct S { u8 n; u8 data[n]; char name[n + 1]; };\nS s @ 0;",
+            &mut mem,
+        )
+        .unwrap();
+
+        let s = &nodes[0];
+        assert_eq!(s.children[1].type_name, "u8[3]");
+        assert_eq!(s.children[1].children[2].value, Value::Int(3));
+        assert_eq!(s.children[1].children[2].range, 3..4);
+        assert_eq!(s.children[2].value, Value::Text("hi".to_owned()));
+        assert_eq!(s.range, 0..8);
+    }
+
+    #[test]
+    fn enums_bitfields_and_conditionals() {
+        let src = "
+            enum Kind : u8 { A, B = 5, C };
+            bitfield Flags : u8 { lo : 4; _ : 2; hi : 2; };
+            struct S {
+                Kind kind;
+                Flags flags;
+                if (kind == Kind::A) { u8 a; }
+                else if (kind == Kind::C && flags.hi == 2) { u16 c; }
+                else { u8 other; }
+            };
+            S s @ 0;
+        ";
+
+        let mut mem = [6, 0b1000_0011, 0xAA, 0xBB];
+        let nodes = eval(src, &mut mem).unwrap();
+        let s = &nodes[0];
+        assert_eq!(s.children[0].value, Value::Enum(6, Some("C".to_owned())));
+        assert_eq!(s.children[1].children.len(), 2);
+        assert_eq!(s.children[1].children[0].value, Value::Int(3));
+        assert_eq!(s.children[1].children[1].value, Value::Int(2));
+        assert_eq!(s.children[2].name, "c");
+        assert_eq!(s.children[2].value, Value::Int(0xBBAA));
+
+        let mut mem = [5, 0, 0xAA];
+        let nodes = eval(src, &mut mem).unwrap();
+        assert_eq!(nodes[0].children[2].name, "other");
+    }
+
+    #[test]
+    fn placements_refer_to_each_other() {
+        let mut mem = [4, 0, 0, 0, 0x2A];
+        let nodes = eval(
+            "endian big;\nu8 ptr @ 0;\nu8 val @ ptr;\nu8 next @ $;",
+            &mut mem,
+        );
+        assert_eq!(
+            nodes.unwrap_err(),
+            TemplateError::new(4, "address 5 is not mapped")
+        );
+
+        let nodes = eval("u8 ptr @ 0;\nu8 val @ ptr;", &mut mem).unwrap();
+        assert_eq!(nodes[1].value, Value::Int(0x2A));
+        assert_eq!(nodes[1].range, 4..5);
+    }
+
+    #[test]
+    fn errors_have_lines() {
+        let err = Template::parse("struct S {\n  u8 a\n};").unwrap_err();
+        assert_eq!(err.line, 3);
+
+        let err = eval("struct S {\n  u8 a[b];\n};\nS s @ 0;", &mut [0]).unwrap_err();
+        assert_eq!(err, TemplateError::new(2, "unknown field `b`"));
+
+        let err = eval("struct S { S s; };\nS s @ 0;", &mut [0]).unwrap_err();
+        assert_eq!(err.message, "structs are nested too deep");
+
+        let err = Template::parse("enum E : f32 { A };").unwrap_err();
+        assert_eq!(err.message, "expected an integer type");
+    }
+}
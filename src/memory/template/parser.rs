@@ -0,0 +1,535 @@
+use super::{
+    lexer::{lex, Token},
+    TemplateError,
+};
+use crate::memory::{DataType, Endianness};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Prim {
+    Data(DataType),
+    Char,
+    Bool,
+}
+
+impl Prim {
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        if let Some(typ) = DataType::ALL.into_iter().find(|typ| typ.desc() == name) {
+            return Some(Prim::Data(typ));
+        }
+        match name {
+            "char" => Some(Prim::Char),
+            "bool" => Some(Prim::Bool),
+            _ => None,
+        }
+    }
+
+    pub(super) fn size(&self) -> usize {
+        match self {
+            Prim::Data(typ) => typ.size(),
+            Prim::Char | Prim::Bool => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum UnOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
+impl UnOp {
+    pub(super) fn apply(&self, val: i128) -> i128 {
+        match self {
+            UnOp::Neg => val.wrapping_neg(),
+            UnOp::Not => (val == 0) as i128,
+            UnOp::BitNot => !val,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum BinOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Shl,
+    Shr,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinOp {
+    fn from_punct(punct: &str) -> Option<(Self, u8)> {
+        Some(match punct {
+            "||" => (BinOp::Or, 1),
+            "&&" => (BinOp::And, 2),
+            "|" => (BinOp::BitOr, 3),
+            "^" => (BinOp::BitXor, 4),
+            "&" => (BinOp::BitAnd, 5),
+            "==" => (BinOp::Eq, 6),
+            "!=" => (BinOp::Ne, 6),
+            "<" => (BinOp::Lt, 7),
+            "<=" => (BinOp::Le, 7),
+            ">" => (BinOp::Gt, 7),
+            ">=" => (BinOp::Ge, 7),
+            "<<" => (BinOp::Shl, 8),
+            ">>" => (BinOp::Shr, 8),
+            "+" => (BinOp::Add, 9),
+            "-" => (BinOp::Sub, 9),
+            "*" => (BinOp::Mul, 10),
+            "/" => (BinOp::Div, 10),
+            "%" => (BinOp::Rem, 10),
+            _ => return None,
+        })
+    }
+
+    /// Returns `None` on division by zero.
+    pub(super) fn apply(&self, a: i128, b: i128) -> Option<i128> {
+        Some(match self {
+            BinOp::Or => (a != 0 || b != 0) as i128,
+            BinOp::And => (a != 0 && b != 0) as i128,
+            BinOp::BitOr => a | b,
+            BinOp::BitXor => a ^ b,
+            BinOp::BitAnd => a & b,
+            BinOp::Eq => (a == b) as i128,
+            BinOp::Ne => (a != b) as i128,
+            BinOp::Lt => (a < b) as i128,
+            BinOp::Le => (a <= b) as i128,
+            BinOp::Gt => (a > b) as i128,
+            BinOp::Ge => (a >= b) as i128,
+            BinOp::Shl => a.wrapping_shl(b as u32),
+            BinOp::Shr => a.wrapping_shr(b as u32),
+            BinOp::Add => a.wrapping_add(b),
+            BinOp::Sub => a.wrapping_sub(b),
+            BinOp::Mul => a.wrapping_mul(b),
+            BinOp::Div => a.checked_div(b)?,
+            BinOp::Rem => a.checked_rem(b)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+    Int(i128),
+    Dollar,
+    Ident(String),
+    EnumConst(String, String),
+    Member(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Field {
+    pub(super) type_name: String,
+    pub(super) endian: Option<Endianness>,
+    pub(super) name: String,
+    pub(super) len: Option<Expr>,
+    pub(super) line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Stmt {
+    Field(Field),
+    If {
+        /// `if` and `else if` conditions with their bodies
+        branches: Vec<(Expr, Vec<Stmt>)>,
+        otherwise: Vec<Stmt>,
+        line: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct EnumDecl {
+    pub(super) base: DataType,
+    pub(super) variants: Vec<(String, i128)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct BitfieldDecl {
+    pub(super) base: DataType,
+    /// Fields from the least significant bit, `_` is padding.
+    pub(super) fields: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Placement {
+    pub(super) field: Field,
+    pub(super) addr: Expr,
+}
+
+/// A parsed binary template, see [`Template::parse`](super::Template::parse) for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub(super) structs: HashMap<String, Vec<Stmt>>,
+    pub(super) enums: HashMap<String, EnumDecl>,
+    pub(super) bitfields: HashMap<String, BitfieldDecl>,
+    pub(super) placements: Vec<Placement>,
+    pub(super) endian: Endianness,
+}
+
+pub(super) struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    pub(super) fn new(src: &str) -> Result<Self, TemplateError> {
+        Ok(Self {
+            tokens: lex(src)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let tok = self.tokens[self.pos].0.clone();
+        if tok != Token::Eof {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn error(&self, msg: impl Into<String>) -> TemplateError {
+        TemplateError::new(self.line(), msg)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Token::Punct(p) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Token::Ident(name) if name == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), TemplateError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{punct}`")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, TemplateError> {
+        match self.peek().clone() {
+            Token::Ident(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn int_type(&mut self) -> Result<DataType, TemplateError> {
+        match Prim::from_name(&self.ident()?) {
+            Some(Prim::Data(typ)) if !matches!(typ, DataType::F32 | DataType::F64) => Ok(typ),
+            _ => Err(self.error("expected an integer type")),
+        }
+    }
+
+    pub(super) fn template(&mut self) -> Result<Template, TemplateError> {
+        let mut tpl = Template {
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            bitfields: HashMap::new(),
+            placements: Vec::new(),
+            endian: Endianness::Little,
+        };
+
+        while *self.peek() != Token::Eof {
+            let line = self.line();
+            if self.eat_keyword("struct") {
+                let name = self.ident()?;
+                self.expect("{")?;
+                let body = self.block()?;
+                self.eat(";");
+                self.declare(&tpl, &name, line)?;
+                tpl.structs.insert(name, body);
+            } else if self.eat_keyword("enum") {
+                let name = self.ident()?;
+                let decl = self.enum_decl()?;
+                self.declare(&tpl, &name, line)?;
+                tpl.enums.insert(name, decl);
+            } else if self.eat_keyword("bitfield") {
+                let name = self.ident()?;
+                let decl = self.bitfield_decl()?;
+                self.declare(&tpl, &name, line)?;
+                tpl.bitfields.insert(name, decl);
+            } else if self.eat_keyword("endian") {
+                tpl.endian = match self.ident()?.as_str() {
+                    "little" => Endianness::Little,
+                    "big" => Endianness::Big,
+                    _ => return Err(self.error("expected `little` or `big`")),
+                };
+                self.expect(";")?;
+            } else {
+                let field = self.field_decl()?;
+                self.expect("@")?;
+                let addr = self.expr()?;
+                self.expect(";")?;
+                tpl.placements.push(Placement { field, addr });
+            }
+        }
+
+        Ok(tpl)
+    }
+
+    fn declare(&self, tpl: &Template, name: &str, line: usize) -> Result<(), TemplateError> {
+        if Prim::from_name(name).is_some()
+            || tpl.structs.contains_key(name)
+            || tpl.enums.contains_key(name)
+            || tpl.bitfields.contains_key(name)
+        {
+            Err(TemplateError::new(
+                line,
+                format!("type `{name}` is already defined"),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn enum_decl(&mut self) -> Result<EnumDecl, TemplateError> {
+        self.expect(":")?;
+        let base = self.int_type()?;
+        self.expect("{")?;
+
+        let mut variants = Vec::new();
+        let mut next = 0;
+        while !self.eat("}") {
+            let name = self.ident()?;
+            if self.eat("=") {
+                let line = self.line();
+                next = const_eval(&self.expr()?)
+                    .ok_or_else(|| TemplateError::new(line, "expected a constant"))?;
+            }
+            variants.push((name, next));
+            next += 1;
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+        self.eat(";");
+
+        Ok(EnumDecl { base, variants })
+    }
+
+    fn bitfield_decl(&mut self) -> Result<BitfieldDecl, TemplateError> {
+        self.expect(":")?;
+        let base = self.int_type()?;
+        self.expect("{")?;
+
+        let mut fields = Vec::new();
+        let mut total = 0;
+        while !self.eat("}") {
+            let name = self.ident()?;
+            self.expect(":")?;
+            let bits = match *self.peek() {
+                Token::Int(bits @ 1..=64) => bits as u32,
+                _ => return Err(self.error("expected a bit count")),
+            };
+            self.next();
+            total += bits;
+            if total as usize > base.size() * 8 {
+                return Err(self.error(format!("fields don't fit in `{}`", base.desc())));
+            }
+            self.expect(";")?;
+            fields.push((name, bits));
+        }
+        self.eat(";");
+
+        Ok(BitfieldDecl { base, fields })
+    }
+
+    /// Statements up to the closing `}`.
+    fn block(&mut self) -> Result<Vec<Stmt>, TemplateError> {
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            if *self.peek() == Token::Eof {
+                return Err(self.error("expected `}`"));
+            }
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, TemplateError> {
+        let line = self.line();
+        if !self.eat_keyword("if") {
+            let field = self.field_decl()?;
+            self.expect(";")?;
+            return Ok(Stmt::Field(field));
+        }
+
+        let mut branches = Vec::new();
+        let mut otherwise = Vec::new();
+        loop {
+            self.expect("(")?;
+            let cond = self.expr()?;
+            self.expect(")")?;
+            self.expect("{")?;
+            branches.push((cond, self.block()?));
+
+            if !self.eat_keyword("else") {
+                break;
+            }
+            if !self.eat_keyword("if") {
+                self.expect("{")?;
+                otherwise = self.block()?;
+                break;
+            }
+        }
+
+        Ok(Stmt::If {
+            branches,
+            otherwise,
+            line,
+        })
+    }
+
+    /// `[le|be] Type name[len]`
+    fn field_decl(&mut self) -> Result<Field, TemplateError> {
+        let line = self.line();
+        let mut endian = None;
+        if self.eat_keyword("le") {
+            endian = Some(Endianness::Little);
+        } else if self.eat_keyword("be") {
+            endian = Some(Endianness::Big);
+        }
+
+        let type_name = self.ident()?;
+        let name = self.ident()?;
+        let len = if self.eat("[") {
+            let len = self.expr()?;
+            self.expect("]")?;
+            Some(len)
+        } else {
+            None
+        };
+
+        Ok(Field {
+            type_name,
+            endian,
+            name,
+            len,
+            line,
+        })
+    }
+
+    pub(super) fn expr(&mut self) -> Result<Expr, TemplateError> {
+        self.binary(1)
+    }
+
+    fn binary(&mut self, min_prec: u8) -> Result<Expr, TemplateError> {
+        let mut lhs = self.unary()?;
+        while let Some((op, prec)) = self.binary_op(min_prec) {
+            self.pos += 1;
+            let rhs = self.binary(prec + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn binary_op(&self, min_prec: u8) -> Option<(BinOp, u8)> {
+        match self.peek() {
+            Token::Punct(p) => BinOp::from_punct(p).filter(|(_, prec)| *prec >= min_prec),
+            _ => None,
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, TemplateError> {
+        let op = if self.eat("-") {
+            UnOp::Neg
+        } else if self.eat("!") {
+            UnOp::Not
+        } else if self.eat("~") {
+            UnOp::BitNot
+        } else {
+            return self.postfix();
+        };
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn postfix(&mut self) -> Result<Expr, TemplateError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                expr = Expr::Member(Box::new(expr), self.ident()?);
+            } else if self.eat("[") {
+                let idx = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(idx));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, TemplateError> {
+        if !matches!(
+            self.peek(),
+            Token::Int(_) | Token::Dollar | Token::Ident(_) | Token::Punct("(")
+        ) {
+            return Err(self.error("expected an expression"));
+        }
+
+        match self.next() {
+            Token::Int(val) => Ok(Expr::Int(val as i128)),
+            Token::Dollar => Ok(Expr::Dollar),
+            Token::Ident(name) => {
+                if self.eat("::") {
+                    Ok(Expr::EnumConst(name, self.ident()?))
+                } else {
+                    Ok(Expr::Ident(name))
+                }
+            }
+            _ => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+        }
+    }
+}
+
+/// Evaluates an expression made only of literals.
+fn const_eval(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Int(val) => Some(*val),
+        Expr::Unary(op, val) => Some(op.apply(const_eval(val)?)),
+        Expr::Binary(op, a, b) => op.apply(const_eval(a)?, const_eval(b)?),
+        _ => None,
+    }
+}
@@ -0,0 +1,347 @@
+use std::mem::size_of;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DataFormat {
+    Bin,
+    Dec,
+    Hex,
+}
+
+impl DataFormat {
+    pub const ALL: [Self; 3] = [Self::Dec, Self::Hex, Self::Bin];
+
+    pub(super) fn desc(&self) -> &'static str {
+        match self {
+            DataFormat::Bin => "bin",
+            DataFormat::Dec => "dec",
+            DataFormat::Hex => "hex",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DataType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+}
+
+impl DataType {
+    pub const ALL: [Self; 10] = [
+        Self::I8,
+        Self::I16,
+        Self::I32,
+        Self::I64,
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::F32,
+        Self::F64,
+    ];
+
+    pub fn size(&self) -> usize {
+        match self {
+            DataType::I8 => size_of::<i8>(),
+            DataType::I16 => size_of::<i16>(),
+            DataType::I32 => size_of::<i32>(),
+            DataType::I64 => size_of::<i64>(),
+            DataType::U8 => size_of::<u8>(),
+            DataType::U16 => size_of::<u16>(),
+            DataType::U32 => size_of::<u32>(),
+            DataType::U64 => size_of::<u64>(),
+            DataType::F32 => size_of::<f32>(),
+            DataType::F64 => size_of::<f64>(),
+        }
+    }
+
+    pub(super) fn desc(&self) -> &'static str {
+        match self {
+            DataType::I8 => "i8",
+            DataType::I16 => "i16",
+            DataType::I32 => "i32",
+            DataType::I64 => "i64",
+            DataType::U8 => "u8",
+            DataType::U16 => "u16",
+            DataType::U32 => "u32",
+            DataType::U64 => "u64",
+            DataType::F32 => "f32",
+            DataType::F64 => "f64",
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub const ALL: [Self; 2] = [Self::Little, Self::Big];
+
+    pub(super) fn desc(&self) -> &'static str {
+        match self {
+            Endianness::Little => "LE",
+            Endianness::Big => "BE",
+        }
+    }
+}
+
+/// Reads `data_type` from the start of `bytes` as a little endian `u64`.
+pub(super) fn to_raw(bytes: &[u8], data_type: DataType, endian: Endianness) -> u64 {
+    let size = data_type.size();
+    let mut buf = [0; 8];
+    buf[..size].copy_from_slice(&bytes[..size]);
+    if endian == Endianness::Big {
+        buf[..size].reverse();
+    }
+    u64::from_le_bytes(buf)
+}
+
+/// Formats the value stored at the start of `bytes`, which must hold at least
+/// `data_type.size()` bytes.
+pub(super) fn format_value(
+    bytes: &[u8],
+    data_type: DataType,
+    data_format: DataFormat,
+    endian: Endianness,
+) -> String {
+    let size = data_type.size();
+    let raw = to_raw(bytes, data_type, endian);
+
+    match data_format {
+        DataFormat::Bin => format_binary(&raw.to_le_bytes(), size * 8),
+        DataFormat::Hex => format!("0x{:0width$X}", raw, width = size * 2),
+        DataFormat::Dec => match data_type {
+            DataType::F32 => format!("{}", f32::from_bits(raw as u32)),
+            DataType::F64 => format!("{}", f64::from_bits(raw)),
+            _ if data_type.is_signed() => {
+                let shift = 64 - size * 8;
+                format!("{}", ((raw << shift) as i64) >> shift)
+            }
+            _ => format!("{raw}"),
+        },
+    }
+}
+
+/// Parses `text` in the given format into the bytes of a `data_type` value.
+/// Only the first `data_type.size()` bytes of the result are meaningful.
+pub(super) fn parse_value(
+    text: &str,
+    data_type: DataType,
+    data_format: DataFormat,
+    endian: Endianness,
+) -> Option<[u8; 8]> {
+    let size = data_type.size();
+    let bits = size * 8;
+    let text = text.trim();
+
+    let raw = match data_format {
+        DataFormat::Dec => match data_type {
+            DataType::F32 => text.parse::<f32>().ok()?.to_bits() as u64,
+            DataType::F64 => text.parse::<f64>().ok()?.to_bits(),
+            _ if data_type.is_signed() => {
+                let val = text.parse::<i64>().ok()?;
+                let shift = 64 - bits;
+                if (val << shift) >> shift != val {
+                    return None;
+                }
+                val as u64
+            }
+            _ => text.parse::<u64>().ok()?,
+        },
+        DataFormat::Hex => {
+            let text = text.trim_start_matches("0x").trim_start_matches("0X");
+            u64::from_str_radix(text, 16).ok()?
+        }
+        DataFormat::Bin => {
+            let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+            u64::from_str_radix(&text, 2).ok()?
+        }
+    };
+
+    let mask = if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    };
+    // Signed decimals were range checked above, everything else must fit in the type's bits
+    let signed_dec = data_format == DataFormat::Dec && data_type.is_signed();
+    if !signed_dec && raw & !mask != 0 {
+        return None;
+    }
+
+    let mut buf = (raw & mask).to_le_bytes();
+    if endian == Endianness::Big {
+        buf[..size].reverse();
+    }
+    Some(buf)
+}
+
+pub(super) fn format_binary(buf: &[u8], width: usize) -> String {
+    let mut out_buf = String::new();
+    let n = width / 8;
+
+    for j in (0..=(n - 1)).rev() {
+        for i in 0..8 {
+            out_buf.push(if (buf[j] & (1 << (7 - i))) != 0 {
+                '1'
+            } else {
+                '0'
+            });
+        }
+        out_buf.push(' ');
+    }
+
+    out_buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, data_type: DataType, data_format: DataFormat) -> Option<Vec<u8>> {
+        parse_value(text, data_type, data_format, Endianness::Little)
+            .map(|buf| buf[..data_type.size()].to_vec())
+    }
+
+    #[test]
+    fn unsigned_range() {
+        assert_eq!(
+            parse("255", DataType::U8, DataFormat::Dec),
+            Some(vec![0xFF])
+        );
+        assert_eq!(parse("256", DataType::U8, DataFormat::Dec), None);
+        assert_eq!(parse("300", DataType::U8, DataFormat::Dec), None);
+        assert_eq!(parse("-1", DataType::U8, DataFormat::Dec), None);
+        assert_eq!(
+            parse("0xFF", DataType::U8, DataFormat::Hex),
+            Some(vec![0xFF])
+        );
+        assert_eq!(parse("0x100", DataType::U8, DataFormat::Hex), None);
+        assert_eq!(
+            parse("1111 1111", DataType::U8, DataFormat::Bin),
+            Some(vec![0xFF])
+        );
+        assert_eq!(parse("1 0000 0000", DataType::U8, DataFormat::Bin), None);
+        assert_eq!(parse("65536", DataType::U16, DataFormat::Dec), None);
+        assert_eq!(
+            parse("18446744073709551615", DataType::U64, DataFormat::Dec),
+            Some(vec![0xFF; 8])
+        );
+        assert_eq!(
+            parse("18446744073709551616", DataType::U64, DataFormat::Dec),
+            None
+        );
+    }
+
+    #[test]
+    fn signed_range() {
+        assert_eq!(
+            parse("-128", DataType::I8, DataFormat::Dec),
+            Some(vec![0x80])
+        );
+        assert_eq!(
+            parse("127", DataType::I8, DataFormat::Dec),
+            Some(vec![0x7F])
+        );
+        assert_eq!(parse("-129", DataType::I8, DataFormat::Dec), None);
+        assert_eq!(parse("128", DataType::I8, DataFormat::Dec), None);
+        assert_eq!(
+            parse("-2", DataType::I16, DataFormat::Dec),
+            Some(vec![0xFE, 0xFF])
+        );
+        // Hex and binary are the raw bits of the value
+        assert_eq!(
+            parse("0x80", DataType::I8, DataFormat::Hex),
+            Some(vec![0x80])
+        );
+        assert_eq!(parse("0x180", DataType::I8, DataFormat::Hex), None);
+    }
+
+    #[test]
+    fn endianness() {
+        let le = parse_value("0x1234", DataType::U16, DataFormat::Hex, Endianness::Little);
+        let be = parse_value("0x1234", DataType::U16, DataFormat::Hex, Endianness::Big);
+        assert_eq!(le.map(|buf| [buf[0], buf[1]]), Some([0x34, 0x12]));
+        assert_eq!(be.map(|buf| [buf[0], buf[1]]), Some([0x12, 0x34]));
+
+        let bytes = [0x12, 0x34, 0x56, 0x78];
+        assert_eq!(
+            format_value(&bytes, DataType::U32, DataFormat::Hex, Endianness::Little),
+            "0x78563412"
+        );
+        assert_eq!(
+            format_value(&bytes, DataType::U32, DataFormat::Hex, Endianness::Big),
+            "0x12345678"
+        );
+    }
+
+    #[test]
+    fn format_values() {
+        assert_eq!(
+            format_value(&[0xFF], DataType::I8, DataFormat::Dec, Endianness::Little),
+            "-1"
+        );
+        assert_eq!(
+            format_value(&[0xFF], DataType::U8, DataFormat::Dec, Endianness::Little),
+            "255"
+        );
+        assert_eq!(
+            format_value(
+                &[0x00, 0x80],
+                DataType::I16,
+                DataFormat::Dec,
+                Endianness::Little
+            ),
+            "-32768"
+        );
+        assert_eq!(
+            format_value(&[0x05], DataType::U8, DataFormat::Bin, Endianness::Little),
+            "00000101 "
+        );
+        assert_eq!(
+            format_value(
+                &1.5f32.to_le_bytes(),
+                DataType::F32,
+                DataFormat::Dec,
+                Endianness::Little
+            ),
+            "1.5"
+        );
+    }
+
+    #[test]
+    fn values_round_trip() {
+        for data_type in DataType::ALL {
+            for data_format in DataFormat::ALL {
+                for endian in Endianness::ALL {
+                    let bytes = [0x81, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x48];
+                    let text = format_value(&bytes, data_type, data_format, endian);
+                    let parsed = parse_value(&text, data_type, data_format, endian);
+                    let size = data_type.size();
+                    assert_eq!(
+                        parsed.map(|buf| buf[..size].to_vec()),
+                        Some(bytes[..size].to_vec()),
+                        "{text} as {data_type:?} {data_format:?} {endian:?}"
+                    );
+                }
+            }
+        }
+    }
+}
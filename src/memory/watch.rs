@@ -0,0 +1,661 @@
+use super::{
+    value::{format_value, parse_value},
+    DataFormat, DataType, Endianness, MemoryEditor, MemorySource,
+};
+use imgui::{ComboBoxFlags, Condition, InputTextFlags, MouseButton, StyleColor, TableFlags, Ui};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// How long the marker next to a changed value stays visible.
+const CHANGE_MARKER_TIME: Duration = Duration::from_millis(1500);
+const CHANGE_MARKER_COLOR: [f32; 3] = [1.0, 0.6, 0.0];
+
+/// Where a watch reads its value from.
+#[derive(Clone, PartialEq)]
+pub enum WatchTarget {
+    Addr(usize),
+    /// A name registered with [`MemoryEditor::set_label`](super::MemoryEditor::set_label).
+    Label(String),
+}
+
+impl WatchTarget {
+    fn resolve(&self, labels: &HashMap<String, usize>) -> Option<usize> {
+        match self {
+            WatchTarget::Addr(addr) => Some(*addr),
+            WatchTarget::Label(name) => labels.get(name).copied(),
+        }
+    }
+}
+
+impl fmt::Display for WatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchTarget::Addr(addr) => write!(f, "0x{addr:X}"),
+            WatchTarget::Label(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Hex addresses are prefixed with `0x`, anything else is a label.
+impl FromStr for WatchTarget {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16).map(WatchTarget::Addr),
+            None => Ok(WatchTarget::Label(s.to_owned())),
+        }
+    }
+}
+
+/// A typed value in memory shown in the watch list.
+#[derive(Clone)]
+pub struct Watch {
+    name: String,
+    target: WatchTarget,
+    data_type: DataType,
+    endian: Endianness,
+    format: DataFormat,
+}
+
+impl Watch {
+    pub fn new(name: &str, target: WatchTarget, data_type: DataType) -> Self {
+        Self {
+            name: name.replace(['\t', '\n'], " "),
+            target,
+            data_type,
+            endian: Endianness::Little,
+            format: DataFormat::Dec,
+        }
+    }
+
+    pub fn endian(mut self, endian: Endianness) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    pub fn format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_target(&self) -> &WatchTarget {
+        &self.target
+    }
+
+    pub fn get_data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    pub fn get_endian(&self) -> Endianness {
+        self.endian
+    }
+
+    pub fn get_format(&self) -> DataFormat {
+        self.format
+    }
+}
+
+struct WatchEntry {
+    watch: Watch,
+    last: Option<[u8; 8]>,
+    changed_at: Option<Instant>,
+    editing: bool,
+    edit_take_focus: bool,
+    edit_buf: String,
+}
+
+impl WatchEntry {
+    fn new(watch: Watch) -> Self {
+        Self {
+            watch,
+            last: None,
+            changed_at: None,
+            editing: false,
+            edit_take_focus: false,
+            edit_buf: String::with_capacity(32),
+        }
+    }
+
+    /// Writes the edited value through the editor, returns whether it was written.
+    fn write_edit<M: MemorySource + ?Sized>(
+        &self,
+        editor: &mut MemoryEditor,
+        mem: &mut M,
+        addr: usize,
+    ) -> bool {
+        let watch = &self.watch;
+        parse_value(&self.edit_buf, watch.data_type, watch.format, watch.endian)
+            .is_some_and(|bytes| editor.write_bytes(mem, addr, &bytes[..watch.data_type.size()]))
+    }
+}
+
+/// Error returned when restoring a [`WatchList`] from text.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseWatchListError {
+    line: usize,
+}
+
+impl fmt::Display for ParseWatchListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid watch on line {}", self.line)
+    }
+}
+
+impl Error for ParseWatchListError {}
+
+/// Watched values, shown live in the "Watch" window.
+///
+/// The list can be saved with [`ToString`] and restored with [`FromStr`], one watch per line
+/// with tab separated fields: `name  target  type  endianness  format`.
+pub struct WatchList {
+    entries: Vec<WatchEntry>,
+    name_input_buf: String,
+    target_input_buf: String,
+    new_data_type: DataType,
+}
+
+impl Default for WatchList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatchList {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            name_input_buf: String::with_capacity(32),
+            target_input_buf: String::with_capacity(32),
+            new_data_type: DataType::I32,
+        }
+    }
+
+    pub fn push(&mut self, watch: Watch) {
+        self.entries.push(WatchEntry::new(watch));
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Watch {
+        self.entries.remove(idx).watch
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Watch> {
+        self.entries.iter().map(|entry| &entry.watch)
+    }
+
+    /// Draws the watch window, values are read and written through `editor` so they follow its
+    /// staging and write protection. Returns the address of a watch whose address was clicked.
+    pub(super) fn draw_window<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        editor: &mut MemoryEditor,
+        mem: &mut M,
+        opened: &mut bool,
+    ) -> Option<usize> {
+        let mut clicked = None;
+
+        ui.window("Watch")
+            .size([480.0, 240.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                self.draw_add(ui, editor.data_preview_adr);
+
+                if ui.button("Copy list") {
+                    ui.set_clipboard_text(self.to_string());
+                }
+                ui.same_line();
+                if ui.button("Paste list") {
+                    if let Some(list) = ui
+                        .clipboard_text()
+                        .and_then(|text| text.parse::<WatchList>().ok())
+                    {
+                        self.entries = list.entries;
+                    }
+                }
+                ui.separator();
+
+                clicked = self.draw_table(ui, editor, mem);
+            });
+
+        clicked
+    }
+
+    fn draw_add(&mut self, ui: &Ui, cursor_addr: usize) {
+        let width = ui.calc_text_size("0000000000000000")[0];
+
+        ui.set_next_item_width(width);
+        ui.input_text("Name", &mut self.name_input_buf).build();
+        ui.same_line();
+        ui.set_next_item_width(width);
+        ui.input_text("Target", &mut self.target_input_buf)
+            .hint("0x1234 or label")
+            .build();
+        ui.same_line();
+        ui.set_next_item_width(ui.calc_text_size("0000")[0] * 1.5);
+        if let Some(_t) = ui.begin_combo_with_flags(
+            "##new_type",
+            self.new_data_type.desc(),
+            ComboBoxFlags::HEIGHT_LARGEST,
+        ) {
+            for typ in DataType::ALL {
+                if ui
+                    .selectable_config(typ.desc())
+                    .selected(self.new_data_type == typ)
+                    .build()
+                {
+                    self.new_data_type = typ;
+                }
+            }
+        }
+
+        let mut target = None;
+        ui.same_line();
+        if ui.button("Add") {
+            target = self
+                .target_input_buf
+                .parse::<WatchTarget>()
+                .ok()
+                .filter(|target| *target != WatchTarget::Label(String::new()));
+        }
+        if cursor_addr != usize::MAX {
+            ui.same_line();
+            if ui.button("Add cursor") {
+                target = Some(WatchTarget::Addr(cursor_addr));
+            }
+        }
+
+        if let Some(target) = target {
+            let name = if self.name_input_buf.is_empty() {
+                target.to_string()
+            } else {
+                self.name_input_buf.clone()
+            };
+            self.push(Watch::new(&name, target, self.new_data_type));
+            self.name_input_buf.clear();
+        }
+    }
+
+    fn draw_table<M: MemorySource + ?Sized>(
+        &mut self,
+        ui: &Ui,
+        editor: &mut MemoryEditor,
+        mem: &mut M,
+    ) -> Option<usize> {
+        let mut clicked = None;
+        let mut removed = None;
+
+        let flags = TableFlags::BORDERS_INNER_V
+            | TableFlags::ROW_BG
+            | TableFlags::RESIZABLE
+            | TableFlags::SIZING_FIXED_FIT
+            | TableFlags::SCROLL_Y;
+        let _table = ui.begin_table_with_flags("##watches", 7, flags)?;
+
+        ui.table_setup_scroll_freeze(0, 1);
+        ui.table_setup_column("Name");
+        ui.table_setup_column("Target");
+        ui.table_setup_column("Type");
+        ui.table_setup_column("Endian");
+        ui.table_setup_column("Format");
+        ui.table_setup_column("Value");
+        ui.table_setup_column("##remove");
+        ui.table_headers_row();
+
+        let now = Instant::now();
+
+        for (idx, entry) in self.entries.iter_mut().enumerate() {
+            let _id = ui.push_id_usize(idx);
+            let watch = &mut entry.watch;
+            let size = watch.data_type.size();
+            let addr = watch.target.resolve(&editor.labels);
+
+            let mut buf = [0; 8];
+            let value = addr.filter(|&addr| editor.read_bytes(mem, addr, &mut buf[..size]));
+            if value.is_some() {
+                if matches!(entry.last, Some(last) if last[..size] != buf[..size]) {
+                    entry.changed_at = Some(now);
+                }
+                entry.last = Some(buf);
+            } else {
+                entry.last = None;
+            }
+
+            ui.table_next_row();
+
+            ui.table_next_column();
+            ui.text(&watch.name);
+
+            ui.table_next_column();
+            match (&watch.target, addr) {
+                (WatchTarget::Label(name), Some(addr)) => ui.text(format!("{name} (0x{addr:X})")),
+                (WatchTarget::Label(name), None) => ui.text_disabled(name),
+                (WatchTarget::Addr(addr), _) => ui.text(format!("0x{addr:X}")),
+            }
+            if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Left) {
+                clicked = addr;
+            }
+
+            let mut changed = false;
+
+            ui.table_next_column();
+            ui.set_next_item_width(ui.calc_text_size("0000")[0] * 1.5);
+            if let Some(_t) = ui.begin_combo_with_flags(
+                "##type",
+                watch.data_type.desc(),
+                ComboBoxFlags::HEIGHT_LARGEST | ComboBoxFlags::NO_ARROW_BUTTON,
+            ) {
+                for typ in DataType::ALL {
+                    if ui
+                        .selectable_config(typ.desc())
+                        .selected(watch.data_type == typ)
+                        .build()
+                    {
+                        changed |= watch.data_type != typ;
+                        watch.data_type = typ;
+                    }
+                }
+            }
+
+            ui.table_next_column();
+            ui.set_next_item_width(ui.calc_text_size("000")[0] * 1.5);
+            if let Some(_t) = ui.begin_combo_with_flags(
+                "##endian",
+                watch.endian.desc(),
+                ComboBoxFlags::NO_ARROW_BUTTON,
+            ) {
+                for endian in Endianness::ALL {
+                    if ui
+                        .selectable_config(endian.desc())
+                        .selected(watch.endian == endian)
+                        .build()
+                    {
+                        changed |= watch.endian != endian;
+                        watch.endian = endian;
+                    }
+                }
+            }
+
+            ui.table_next_column();
+            ui.set_next_item_width(ui.calc_text_size("000")[0] * 1.5);
+            if let Some(_t) = ui.begin_combo_with_flags(
+                "##format",
+                watch.format.desc(),
+                ComboBoxFlags::NO_ARROW_BUTTON,
+            ) {
+                for format in DataFormat::ALL {
+                    if ui
+                        .selectable_config(format.desc())
+                        .selected(watch.format == format)
+                        .build()
+                    {
+                        watch.format = format;
+                    }
+                }
+            }
+
+            // A different interpretation of the same bytes isn't a change in memory
+            if changed {
+                entry.last = None;
+                entry.changed_at = None;
+            }
+
+            ui.table_next_column();
+            let marker_alpha = entry.changed_at.map_or(0.0, |changed_at| {
+                1.0 - (now - changed_at).as_secs_f32() / CHANGE_MARKER_TIME.as_secs_f32()
+            });
+            {
+                let pos = ui.cursor_screen_pos();
+                let height = ui.text_line_height();
+                let [r, g, b] = CHANGE_MARKER_COLOR;
+                if marker_alpha > 0.0 {
+                    ui.get_window_draw_list()
+                        .add_rect(
+                            pos,
+                            [pos[0] + height * 0.4, pos[1] + height],
+                            [r, g, b, marker_alpha],
+                        )
+                        .filled(true)
+                        .build();
+                } else {
+                    entry.changed_at = None;
+                }
+                ui.set_cursor_screen_pos([pos[0] + height * 0.6, pos[1]]);
+            }
+
+            match (addr, value) {
+                (Some(addr), Some(_)) if entry.editing => {
+                    if entry.edit_take_focus {
+                        ui.set_keyboard_focus_here();
+                        entry.edit_take_focus = false;
+                    }
+                    ui.set_next_item_width(-1.0);
+                    if ui
+                        .input_text("##value", &mut entry.edit_buf)
+                        .flags(InputTextFlags::ENTER_RETURNS_TRUE | InputTextFlags::AUTO_SELECT_ALL)
+                        .build()
+                    {
+                        entry.write_edit(editor, mem, addr);
+                        entry.editing = false;
+                    } else if ui.is_item_deactivated() {
+                        entry.editing = false;
+                    }
+                }
+                (_, Some(_)) => {
+                    let text = format_value(&buf, watch.data_type, watch.format, watch.endian);
+                    if marker_alpha > 0.0 {
+                        let [r, g, b] = CHANGE_MARKER_COLOR;
+                        ui.text_colored([r, g, b, 1.0], &text);
+                    } else {
+                        ui.text(&text);
+                    }
+                    let writable = addr.is_some_and(|addr| {
+                        (0..size).all(|i| editor.is_writable(addr.wrapping_add(i)))
+                    });
+                    if writable
+                        && ui.is_item_hovered()
+                        && ui.is_mouse_double_clicked(MouseButton::Left)
+                    {
+                        entry.editing = true;
+                        entry.edit_take_focus = true;
+                        entry.edit_buf = text;
+                    }
+                }
+                _ => {
+                    let _color = ui.push_style_color(StyleColor::Text, [1.0, 0.3, 0.3, 1.0]);
+                    ui.text("N/A");
+                }
+            }
+
+            ui.table_next_column();
+            if ui.small_button("X") {
+                removed = Some(idx);
+            }
+        }
+
+        if let Some(idx) = removed {
+            self.entries.remove(idx);
+        }
+
+        clicked
+    }
+}
+
+impl fmt::Display for WatchList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for watch in self.iter() {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}",
+                watch.name,
+                watch.target,
+                watch.data_type.desc(),
+                watch.endian.desc(),
+                watch.format.desc()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for WatchList {
+    type Err = ParseWatchListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut list = WatchList::new();
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let err = ParseWatchListError { line: i + 1 };
+            let mut fields = line.split('\t');
+            let mut field = || fields.next().ok_or(err);
+
+            let name = field()?;
+            let target = field()?.parse::<WatchTarget>().map_err(|_| err)?;
+            let data_type = field()?;
+            let data_type = DataType::ALL
+                .into_iter()
+                .find(|typ| typ.desc() == data_type)
+                .ok_or(err)?;
+            let endian = field()?;
+            let endian = Endianness::ALL
+                .into_iter()
+                .find(|e| e.desc() == endian)
+                .ok_or(err)?;
+            let format = field()?;
+            let format = DataFormat::ALL
+                .into_iter()
+                .find(|f| f.desc() == format)
+                .ok_or(err)?;
+
+            list.push(
+                Watch::new(name, target, data_type)
+                    .endian(endian)
+                    .format(format),
+            );
+        }
+
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_round_trip() {
+        let mut list = WatchList::new();
+        list.push(Watch::new("hp", WatchTarget::Addr(0x7E0010), DataType::U16));
+        list.push(
+            Watch::new(
+                "pos\tx",
+                WatchTarget::Label("player".to_owned()),
+                DataType::F32,
+            )
+            .endian(Endianness::Big)
+            .format(DataFormat::Hex),
+        );
+
+        let text = list.to_string();
+        assert_eq!(
+            text,
+            "hp\t0x7E0010\tu16\tLE\tdec\npos x\tplayer\tf32\tBE\thex\n"
+        );
+
+        let parsed = text.parse::<WatchList>().unwrap();
+        assert_eq!(parsed.to_string(), text);
+        let watches: Vec<_> = parsed.iter().collect();
+        assert_eq!(watches.len(), 2);
+        assert!(*watches[1].get_target() == WatchTarget::Label("player".to_owned()));
+        assert_eq!(watches[1].get_data_type(), DataType::F32);
+        assert_eq!(watches[1].get_endian(), Endianness::Big);
+        assert_eq!(watches[1].get_format(), DataFormat::Hex);
+    }
+
+    fn edit(watch: Watch, text: &str) -> WatchEntry {
+        let mut entry = WatchEntry::new(watch);
+        entry.edit_buf = text.to_owned();
+        entry
+    }
+
+    #[test]
+    fn edits_respect_protection() {
+        let mut editor = MemoryEditor::new();
+        editor.set_writable_fn(Some(|addr| addr != 5));
+        let mut mem = [0u8; 8];
+
+        let entry = edit(
+            Watch::new("a", WatchTarget::Addr(4), DataType::U16).format(DataFormat::Hex),
+            "0x1234",
+        );
+        assert!(!entry.write_edit(&mut editor, &mut mem[..], 4));
+        assert_eq!(mem, [0; 8]);
+
+        assert!(entry.write_edit(&mut editor, &mut mem[..], 2));
+        assert_eq!(mem[2..4], [0x34, 0x12]);
+
+        editor.set_read_only(true);
+        assert!(!entry.write_edit(&mut editor, &mut mem[..], 0));
+        assert_eq!(mem[..2], [0, 0]);
+    }
+
+    #[test]
+    fn edits_are_staged() {
+        let mut editor = MemoryEditor::new();
+        editor.set_confirm_writes(true);
+        let mut mem = [0u8; 4];
+
+        let entry = edit(Watch::new("a", WatchTarget::Addr(0), DataType::U8), "200");
+        assert!(entry.write_edit(&mut editor, &mut mem[..], 1));
+        assert_eq!(mem, [0; 4]);
+
+        let mut buf = [0; 2];
+        assert!(editor.read_bytes(&mut mem[..], 0, &mut buf));
+        assert_eq!(buf, [0, 200]);
+
+        editor.commit(&mut mem[..]);
+        assert_eq!(mem, [0, 200, 0, 0]);
+    }
+
+    #[test]
+    fn reads_stop_at_the_end() {
+        let mut editor = MemoryEditor::new();
+        let mut mem = [1u8, 2, 3, 4];
+        let mut buf = [0; 4];
+        assert!(editor.read_bytes(&mut mem[..], 0, &mut buf));
+        assert_eq!(buf, [1, 2, 3, 4]);
+
+        // An i32 watch on the last byte, and one past the end
+        assert!(!editor.read_bytes(&mut mem[..], 3, &mut buf));
+        assert!(!editor.read_bytes(&mut mem[..], 4, &mut buf));
+        assert!(!editor.read_bytes(&mut mem[..], usize::MAX, &mut buf));
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let err = "a\t0x10\tu8\tLE\tdec\n\nb\t0x20\tu9\tLE\tdec"
+            .parse::<WatchList>()
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "invalid watch on line 3");
+        assert!("a\t0x10\tu8".parse::<WatchList>().is_err());
+        assert!("a\t0xZZ\tu8\tLE\tdec".parse::<WatchList>().is_err());
+    }
+}
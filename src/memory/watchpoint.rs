@@ -0,0 +1,595 @@
+use imgui::{ComboBoxFlags, Condition, InputTextFlags, MouseButton, TableFlags, Ui};
+use std::{
+    collections::VecDeque,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+/// How long the bytes of a reported hit stay highlighted.
+const HIT_FLASH_TIME: Duration = Duration::from_millis(1500);
+const MAX_LOGGED_HITS: usize = 64;
+
+const READ_COLOR: [f32; 4] = [0.3, 0.8, 0.3, 1.0];
+const WRITE_COLOR: [f32; 4] = [0.9, 0.3, 0.3, 1.0];
+const READ_WRITE_COLOR: [f32; 4] = [0.9, 0.7, 0.2, 1.0];
+pub(super) const HIT_COLOR: [f32; 3] = [1.0, 0.5, 0.0];
+
+/// A memory access performed by the host.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn desc(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+/// Which accesses trigger a watchpoint.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WatchpointKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl WatchpointKind {
+    pub const ALL: [Self; 3] = [Self::Read, Self::Write, Self::ReadWrite];
+
+    fn desc(&self) -> &'static str {
+        match self {
+            WatchpointKind::Read => "Read",
+            WatchpointKind::Write => "Write",
+            WatchpointKind::ReadWrite => "Read/Write",
+        }
+    }
+
+    pub fn matches(&self, access: Access) -> bool {
+        match self {
+            WatchpointKind::Read => access == Access::Read,
+            WatchpointKind::Write => access == Access::Write,
+            WatchpointKind::ReadWrite => true,
+        }
+    }
+
+    pub(super) fn color(&self) -> [f32; 4] {
+        match self {
+            WatchpointKind::Read => READ_COLOR,
+            WatchpointKind::Write => WRITE_COLOR,
+            WatchpointKind::ReadWrite => READ_WRITE_COLOR,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+}
+
+impl Comparison {
+    pub const ALL: [Self; 4] = [Self::Equal, Self::NotEqual, Self::Less, Self::Greater];
+
+    fn desc(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::Greater => ">",
+        }
+    }
+}
+
+/// Only trigger a watchpoint when the accessed value compares to `value`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ValueCondition {
+    pub cmp: Comparison,
+    pub value: u64,
+}
+
+impl ValueCondition {
+    pub fn matches(&self, value: u64) -> bool {
+        match self.cmp {
+            Comparison::Equal => value == self.value,
+            Comparison::NotEqual => value != self.value,
+            Comparison::Less => value < self.value,
+            Comparison::Greater => value > self.value,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct WatchpointId(usize);
+
+#[derive(Clone)]
+pub struct Watchpoint {
+    id: WatchpointId,
+    range: Range<usize>,
+    kind: WatchpointKind,
+    condition: Option<ValueCondition>,
+    enabled: bool,
+    hit_count: u64,
+}
+
+impl Watchpoint {
+    pub fn id(&self) -> WatchpointId {
+        self.id
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn kind(&self) -> WatchpointKind {
+        self.kind
+    }
+
+    pub fn condition(&self) -> Option<ValueCondition> {
+        self.condition
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn hit_count(&self) -> u64 {
+        self.hit_count
+    }
+
+    /// Checks an access of `size` bytes at `addr`, `value` being the value read or written.
+    /// The condition's value is truncated to the access size, so -1 matches a byte of 0xFF.
+    pub fn matches(&self, addr: usize, size: usize, access: Access, value: u64) -> bool {
+        self.enabled
+            && addr < self.range.end
+            && addr.saturating_add(size) > self.range.start
+            && self.kind.matches(access)
+            && self.condition.iter().all(|cond| {
+                let cond = ValueCondition {
+                    value: truncate(cond.value, size),
+                    ..*cond
+                };
+                cond.matches(truncate(value, size))
+            })
+    }
+}
+
+/// A watchpoint hit reported by the host.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchpointHit {
+    pub id: WatchpointId,
+    pub addr: usize,
+    pub size: usize,
+    pub access: Access,
+    pub value: u64,
+}
+
+/// The watchpoints set from the "Watchpoints" window, for the host to check its memory
+/// accesses against.
+pub struct Watchpoints {
+    list: Vec<Watchpoint>,
+    next_id: usize,
+    hits: VecDeque<WatchpointHit>,
+    last_hit: Option<(Range<usize>, Instant)>,
+    jump_on_hit: bool,
+    start_input_buf: String,
+    size_input_buf: String,
+    value_input_buf: String,
+    new_kind: WatchpointKind,
+    new_cmp: Option<Comparison>,
+}
+
+impl Default for Watchpoints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchpoints {
+    pub fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            next_id: 0,
+            hits: VecDeque::with_capacity(MAX_LOGGED_HITS),
+            last_hit: None,
+            jump_on_hit: true,
+            start_input_buf: String::with_capacity(32),
+            size_input_buf: String::from("1"),
+            value_input_buf: String::with_capacity(32),
+            new_kind: WatchpointKind::Write,
+            new_cmp: None,
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        range: Range<usize>,
+        kind: WatchpointKind,
+        condition: Option<ValueCondition>,
+    ) -> WatchpointId {
+        let id = WatchpointId(self.next_id);
+        self.next_id += 1;
+        self.list.push(Watchpoint {
+            id,
+            range,
+            kind,
+            condition,
+            enabled: true,
+            hit_count: 0,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: WatchpointId) -> Option<Watchpoint> {
+        let idx = self.list.iter().position(|wp| wp.id == id)?;
+        Some(self.list.remove(idx))
+    }
+
+    pub fn set_enabled(&mut self, id: WatchpointId, enabled: bool) {
+        if let Some(wp) = self.list.iter_mut().find(|wp| wp.id == id) {
+            wp.enabled = enabled;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn get(&self, id: WatchpointId) -> Option<&Watchpoint> {
+        self.list.iter().find(|wp| wp.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Watchpoint> {
+        self.list.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the first enabled watchpoint triggered by an access.
+    pub fn find(
+        &self,
+        addr: usize,
+        size: usize,
+        access: Access,
+        value: u64,
+    ) -> Option<WatchpointId> {
+        self.list
+            .iter()
+            .find(|wp| wp.matches(addr, size, access, value))
+            .map(|wp| wp.id)
+    }
+
+    /// Records a hit, returns whether the editor should jump to it.
+    pub(super) fn report(&mut self, hit: WatchpointHit) -> bool {
+        if let Some(wp) = self.list.iter_mut().find(|wp| wp.id == hit.id) {
+            wp.hit_count += 1;
+        }
+        if self.hits.len() == MAX_LOGGED_HITS {
+            self.hits.pop_front();
+        }
+        self.hits.push_back(hit);
+        self.last_hit = Some((
+            hit.addr..hit.addr.saturating_add(hit.size.max(1)),
+            Instant::now(),
+        ));
+        self.jump_on_hit
+    }
+
+    /// The watchpoint covering `addr`, if any is enabled.
+    pub(super) fn at(&self, addr: usize) -> Option<&Watchpoint> {
+        self.list
+            .iter()
+            .find(|wp| wp.enabled && wp.range.contains(&addr))
+    }
+
+    /// Opacity of the flash over `addr` if it was part of the last hit.
+    pub(super) fn hit_flash(&self, addr: usize) -> Option<f32> {
+        let (range, time) = self.last_hit.as_ref()?;
+        let alpha = 1.0 - time.elapsed().as_secs_f32() / HIT_FLASH_TIME.as_secs_f32();
+        if range.contains(&addr) && alpha > 0.0 {
+            Some(alpha)
+        } else {
+            None
+        }
+    }
+
+    /// Draws the watchpoints window. Returns an address to jump to.
+    pub(super) fn draw_window(
+        &mut self,
+        ui: &Ui,
+        opened: &mut bool,
+        cursor_addr: usize,
+    ) -> Option<usize> {
+        let mut clicked = None;
+
+        ui.window("Watchpoints")
+            .size([420.0, 320.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                self.draw_add(ui, cursor_addr);
+                ui.separator();
+                clicked = self.draw_table(ui);
+                ui.separator();
+                if let Some(addr) = self.draw_hits(ui) {
+                    clicked = Some(addr);
+                }
+            });
+
+        clicked
+    }
+
+    fn draw_add(&mut self, ui: &Ui, cursor_addr: usize) {
+        let width = ui.calc_text_size("0000000000000000")[0];
+
+        ui.set_next_item_width(width);
+        ui.input_text("Start", &mut self.start_input_buf)
+            .flags(InputTextFlags::CHARS_HEXADECIMAL)
+            .build();
+        if cursor_addr != usize::MAX {
+            ui.same_line();
+            if ui.button("Use cursor") {
+                self.start_input_buf = format!("{cursor_addr:X}");
+            }
+        }
+        ui.set_next_item_width(width);
+        ui.input_text("Size", &mut self.size_input_buf)
+            .flags(InputTextFlags::CHARS_DECIMAL)
+            .build();
+
+        ui.set_next_item_width(width);
+        if let Some(_t) = ui.begin_combo("Access", self.new_kind.desc()) {
+            for kind in WatchpointKind::ALL {
+                if ui
+                    .selectable_config(kind.desc())
+                    .selected(self.new_kind == kind)
+                    .build()
+                {
+                    self.new_kind = kind;
+                }
+            }
+        }
+
+        ui.set_next_item_width(ui.calc_text_size("any")[0] * 2.0);
+        let cmp_desc = self.new_cmp.map_or("any", |cmp| cmp.desc());
+        if let Some(_t) =
+            ui.begin_combo_with_flags("##cmp", cmp_desc, ComboBoxFlags::HEIGHT_LARGEST)
+        {
+            if ui
+                .selectable_config("any")
+                .selected(self.new_cmp.is_none())
+                .build()
+            {
+                self.new_cmp = None;
+            }
+            for cmp in Comparison::ALL {
+                if ui
+                    .selectable_config(cmp.desc())
+                    .selected(self.new_cmp == Some(cmp))
+                    .build()
+                {
+                    self.new_cmp = Some(cmp);
+                }
+            }
+        }
+        if self.new_cmp.is_some() {
+            ui.same_line();
+            ui.set_next_item_width(width);
+            ui.input_text("Value", &mut self.value_input_buf)
+                .hint("42 or 0x2A")
+                .build();
+        }
+
+        if ui.button("Add") {
+            let start = usize::from_str_radix(&self.start_input_buf, 16).ok();
+            let size = self.size_input_buf.parse::<usize>().ok().filter(|&n| n > 0);
+            let condition = match self.new_cmp {
+                Some(cmp) => parse_int(&self.value_input_buf)
+                    .map(|value| Some(ValueCondition { cmp, value })),
+                None => Some(None),
+            };
+            if let (Some(start), Some(size), Some(condition)) = (start, size, condition) {
+                self.add(start..start.saturating_add(size), self.new_kind, condition);
+            }
+        }
+    }
+
+    fn draw_table(&mut self, ui: &Ui) -> Option<usize> {
+        let mut clicked = None;
+        let mut removed = None;
+
+        let flags = TableFlags::BORDERS_INNER_V | TableFlags::ROW_BG | TableFlags::SIZING_FIXED_FIT;
+        let _table = ui.begin_table_with_flags("##watchpoints", 6, flags)?;
+
+        ui.table_setup_column("##enabled");
+        ui.table_setup_column("Range");
+        ui.table_setup_column("Access");
+        ui.table_setup_column("Condition");
+        ui.table_setup_column("Hits");
+        ui.table_setup_column("##remove");
+        ui.table_headers_row();
+
+        for wp in self.list.iter_mut() {
+            let _id = ui.push_id_usize(wp.id.0);
+            ui.table_next_row();
+
+            ui.table_next_column();
+            ui.checkbox("##enabled", &mut wp.enabled);
+
+            ui.table_next_column();
+            ui.text_colored(
+                wp.kind.color(),
+                format!("{:X}..{:X}", wp.range.start, wp.range.end),
+            );
+            if ui.is_item_hovered() && ui.is_mouse_clicked(MouseButton::Left) {
+                clicked = Some(wp.range.start);
+            }
+
+            ui.table_next_column();
+            ui.text(wp.kind.desc());
+
+            ui.table_next_column();
+            match wp.condition {
+                Some(cond) => ui.text(format!("{} 0x{:X}", cond.cmp.desc(), cond.value)),
+                None => ui.text_disabled("any"),
+            }
+
+            ui.table_next_column();
+            ui.text(format!("{}", wp.hit_count));
+
+            ui.table_next_column();
+            if ui.small_button("X") {
+                removed = Some(wp.id);
+            }
+        }
+
+        if let Some(id) = removed {
+            self.remove(id);
+        }
+
+        clicked
+    }
+
+    fn draw_hits(&mut self, ui: &Ui) -> Option<usize> {
+        let mut clicked = None;
+
+        ui.checkbox("Jump to hits", &mut self.jump_on_hit);
+        ui.same_line();
+        if ui.button("Clear log") {
+            self.hits.clear();
+        }
+
+        ui.child_window("##hits").build(|| {
+            for hit in self.hits.iter().rev() {
+                let text = format!(
+                    "{:X}: {} of {} bytes, value 0x{:X}",
+                    hit.addr,
+                    hit.access.desc(),
+                    hit.size,
+                    hit.value
+                );
+                if ui.selectable(&text) {
+                    clicked = Some(hit.addr);
+                }
+            }
+        });
+
+        clicked
+    }
+}
+
+/// Keeps the low `size` bytes of `value`.
+fn truncate(value: u64, size: usize) -> u64 {
+    match size {
+        1..=7 => value & ((1 << (size * 8)) - 1),
+        _ => value,
+    }
+}
+
+fn parse_int(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text
+            .parse::<u64>()
+            .ok()
+            .or_else(|| text.parse::<i64>().ok().map(|val| val as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(
+        range: Range<usize>,
+        kind: WatchpointKind,
+        condition: Option<ValueCondition>,
+    ) -> Watchpoint {
+        let mut watchpoints = Watchpoints::new();
+        let id = watchpoints.add(range, kind, condition);
+        watchpoints.get(id).unwrap().clone()
+    }
+
+    #[test]
+    fn range_edges() {
+        let wp = single(0x10..0x14, WatchpointKind::ReadWrite, None);
+        assert!(!wp.matches(0x0F, 1, Access::Read, 0));
+        assert!(wp.matches(0x0F, 2, Access::Read, 0));
+        assert!(wp.matches(0x10, 1, Access::Read, 0));
+        assert!(wp.matches(0x13, 1, Access::Read, 0));
+        assert!(wp.matches(0x08, 0x20, Access::Read, 0));
+        assert!(!wp.matches(0x14, 1, Access::Read, 0));
+        assert!(!wp.matches(0x0C, 4, Access::Read, 0));
+
+        let end = single(usize::MAX - 1..usize::MAX, WatchpointKind::ReadWrite, None);
+        assert!(end.matches(usize::MAX - 1, 4, Access::Read, 0));
+    }
+
+    #[test]
+    fn access_kinds() {
+        for (kind, read, write) in [
+            (WatchpointKind::Read, true, false),
+            (WatchpointKind::Write, false, true),
+            (WatchpointKind::ReadWrite, true, true),
+        ] {
+            let wp = single(0..1, kind, None);
+            assert_eq!(wp.matches(0, 1, Access::Read, 0), read, "{kind:?}");
+            assert_eq!(wp.matches(0, 1, Access::Write, 0), write, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        for (cmp, below, equal, above) in [
+            (Comparison::Equal, false, true, false),
+            (Comparison::NotEqual, true, false, true),
+            (Comparison::Less, true, false, false),
+            (Comparison::Greater, false, false, true),
+        ] {
+            let wp = single(
+                0..4,
+                WatchpointKind::Write,
+                Some(ValueCondition { cmp, value: 42 }),
+            );
+            assert_eq!(wp.matches(0, 1, Access::Write, 41), below, "{cmp:?}");
+            assert_eq!(wp.matches(0, 1, Access::Write, 42), equal, "{cmp:?}");
+            assert_eq!(wp.matches(0, 1, Access::Write, 43), above, "{cmp:?}");
+        }
+    }
+
+    #[test]
+    fn negative_conditions() {
+        let value = parse_int("-1").unwrap();
+        let wp = single(
+            0..8,
+            WatchpointKind::Write,
+            Some(ValueCondition {
+                cmp: Comparison::Equal,
+                value,
+            }),
+        );
+        assert!(wp.matches(0, 1, Access::Write, 0xFF));
+        assert!(wp.matches(0, 2, Access::Write, 0xFFFF));
+        assert!(wp.matches(0, 8, Access::Write, u64::MAX));
+        assert!(!wp.matches(0, 2, Access::Write, 0xFF));
+    }
+
+    #[test]
+    fn disabled_watchpoints_are_skipped() {
+        let mut watchpoints = Watchpoints::new();
+        let first = watchpoints.add(0..4, WatchpointKind::Write, None);
+        let second = watchpoints.add(2..6, WatchpointKind::Write, None);
+        assert_eq!(watchpoints.find(3, 1, Access::Write, 0), Some(first));
+        watchpoints.set_enabled(first, false);
+        assert_eq!(watchpoints.find(3, 1, Access::Write, 0), Some(second));
+        assert_eq!(watchpoints.find(0, 1, Access::Write, 0), None);
+    }
+}
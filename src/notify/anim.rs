@@ -0,0 +1,84 @@
+type EasingFn = fn(t: f32) -> f32;
+
+/// How a toast appears and disappears.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Animation {
+    Fade,
+    /// Slides in from the edge of the screen the toasts are anchored to
+    Slide,
+    /// Grows from half its size while fading
+    Scale,
+    None,
+}
+
+/// Maps the linear progress of an animation, from 0.0 to 1.0, to the eased progress.
+#[derive(Clone, Copy)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Overshoots a little before settling
+    Back,
+    Custom(EasingFn),
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+                }
+            }
+            Easing::Back => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.0;
+                1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+            }
+            Easing::Custom(f) => f(t),
+        }
+    }
+}
+
+/// What an animation does to a toast in a frame.
+pub(super) struct Frame {
+    pub(super) alpha: f32,
+    /// Fraction of the toast's size it is moved towards the edge
+    pub(super) slide: f32,
+    pub(super) scale: f32,
+}
+
+impl Animation {
+    /// The frame at `t`, 0.0 hidden and 1.0 fully shown.
+    pub(super) fn frame(&self, t: f32) -> Frame {
+        match self {
+            Animation::Fade => Frame {
+                alpha: t.clamp(0.0, 1.0),
+                slide: 0.0,
+                scale: 1.0,
+            },
+            Animation::Slide => Frame {
+                alpha: 1.0,
+                slide: 1.0 - t,
+                scale: 1.0,
+            },
+            Animation::Scale => Frame {
+                alpha: t.clamp(0.0, 1.0),
+                slide: 0.0,
+                scale: 0.5 + 0.5 * t,
+            },
+            Animation::None => Frame {
+                alpha: 1.0,
+                slide: 0.0,
+                scale: 1.0,
+            },
+        }
+    }
+}
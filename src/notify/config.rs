@@ -0,0 +1,181 @@
+use imgui::sys::{igFindViewportByID, igGetMainViewport, ImGuiViewport, ImVec2};
+
+use super::{ToastTheme, PADDING_MESSAGE_Y, PADDING_X, PADDING_Y};
+
+/// Where on the viewport toasts are placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// The way new toasts are stacked from the anchor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackDirection {
+    Up,
+    Down,
+}
+
+/// Placement and look of the toasts.
+#[derive(Debug, Clone)]
+pub struct NotificationsConfig {
+    pub anchor: Anchor,
+    /// Down for toasts anchored at the top and up at the bottom when `None`
+    pub direction: Option<StackDirection>,
+    /// Distance from the edges of the viewport's work area
+    pub margin: [f32; 2],
+    /// Vertical space between toasts
+    pub spacing: f32,
+    /// Width text wraps at, a third of the viewport when `None`
+    pub max_width: Option<f32>,
+    /// ID of the viewport to show toasts on, the main viewport when `None` or not found
+    pub viewport: Option<u32>,
+    /// Stops the dismiss timer of the toast under the mouse
+    pub pause_on_hover: bool,
+    /// Toasts shown at once, the rest wait in a queue
+    pub max_visible: Option<usize>,
+    /// Shows toasts with the same type, title and content as one with a counter
+    pub coalesce: bool,
+    /// Moves toasts to their new place in the stack when others come and go, instead of jumping
+    pub smooth_stacking: bool,
+    /// Styles of the toast types, [`ToastTheme::light`] suits imgui's light style
+    pub theme: ToastTheme,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            anchor: Anchor::BottomRight,
+            direction: None,
+            margin: [PADDING_X, PADDING_Y],
+            spacing: PADDING_MESSAGE_Y,
+            max_width: None,
+            viewport: None,
+            pause_on_hover: true,
+            max_visible: None,
+            coalesce: false,
+            smooth_stacking: true,
+            theme: ToastTheme::default(),
+        }
+    }
+}
+
+/// Positions of the toasts for a frame.
+pub(super) struct Layout {
+    /// Where the first toast is placed
+    pub(super) origin: [f32; 2],
+    pub(super) pivot: [f32; 2],
+    /// Direction the next toast is offset in, -1 or 1
+    pub(super) step: f32,
+    /// Direction toasts slide out of the viewport in
+    pub(super) slide: [f32; 2],
+    pub(super) max_width: f32,
+    pub(super) viewport_id: u32,
+    /// Middle of the viewport, where dialogs are placed
+    pub(super) center: [f32; 2],
+}
+
+impl NotificationsConfig {
+    pub(super) fn layout(&self) -> Layout {
+        let viewport: &ImGuiViewport = unsafe {
+            let viewport = self
+                .viewport
+                .map_or(std::ptr::null_mut(), |id| igFindViewportByID(id));
+            if viewport.is_null() {
+                &*igGetMainViewport()
+            } else {
+                &*viewport
+            }
+        };
+        self.layout_in(viewport.WorkPos, viewport.WorkSize, viewport.ID)
+    }
+
+    /// The layout in a viewport's work area.
+    fn layout_in(&self, pos: ImVec2, size: ImVec2, viewport_id: u32) -> Layout {
+        let (x, pivot_x) = match self.anchor {
+            Anchor::TopLeft | Anchor::BottomLeft => (pos.x + self.margin[0], 0.0),
+            Anchor::TopCenter | Anchor::BottomCenter => (pos.x + size.x * 0.5, 0.5),
+            Anchor::TopRight | Anchor::BottomRight => (pos.x + size.x - self.margin[0], 1.0),
+        };
+        let y = match self.anchor {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => pos.y + self.margin[1],
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => {
+                pos.y + size.y - self.margin[1]
+            }
+        };
+        let slide = match self.anchor {
+            Anchor::TopLeft | Anchor::BottomLeft => [-1.0, 0.0],
+            Anchor::TopRight | Anchor::BottomRight => [1.0, 0.0],
+            Anchor::TopCenter => [0.0, -1.0],
+            Anchor::BottomCenter => [0.0, 1.0],
+        };
+        let direction = self.direction.unwrap_or(match self.anchor {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => StackDirection::Down,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => StackDirection::Up,
+        });
+        let (step, pivot_y) = match direction {
+            StackDirection::Up => (-1.0, 1.0),
+            StackDirection::Down => (1.0, 0.0),
+        };
+
+        Layout {
+            origin: [x, y],
+            pivot: [pivot_x, pivot_y],
+            step,
+            slide,
+            max_width: self.max_width.unwrap_or(size.x / 3.0),
+            viewport_id,
+            center: [pos.x + size.x * 0.5, pos.y + size.y * 0.5],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(anchor: Anchor) -> Layout {
+        let config = NotificationsConfig {
+            anchor,
+            ..Default::default()
+        };
+        config.layout_in(ImVec2::new(0.0, 20.0), ImVec2::new(900.0, 600.0), 0)
+    }
+
+    #[test]
+    fn top_anchors_stack_down() {
+        for (anchor, x, pivot_x) in [
+            (Anchor::TopLeft, PADDING_X, 0.0),
+            (Anchor::TopCenter, 450.0, 0.5),
+            (Anchor::TopRight, 900.0 - PADDING_X, 1.0),
+        ] {
+            let layout = layout(anchor);
+            assert_eq!(layout.origin, [x, 20.0 + PADDING_Y], "{anchor:?}");
+            assert_eq!(layout.pivot, [pivot_x, 0.0], "{anchor:?}");
+            assert_eq!(layout.step, 1.0, "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn bottom_anchors_stack_up() {
+        let layout = layout(Anchor::BottomRight);
+        assert_eq!(layout.origin, [900.0 - PADDING_X, 620.0 - PADDING_Y]);
+        assert_eq!(layout.pivot, [1.0, 1.0]);
+        assert_eq!(layout.step, -1.0);
+    }
+
+    #[test]
+    fn direction_can_be_chosen() {
+        let config = NotificationsConfig {
+            anchor: Anchor::TopLeft,
+            direction: Some(StackDirection::Up),
+            ..Default::default()
+        };
+        let layout = config.layout_in(ImVec2::new(0.0, 0.0), ImVec2::new(900.0, 600.0), 0);
+        assert_eq!(layout.step, -1.0);
+    }
+}
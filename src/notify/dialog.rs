@@ -0,0 +1,225 @@
+use imgui::{Key, Ui};
+use std::sync::{Arc, Mutex};
+
+use super::{draw_accent, ToastTheme, ToastType, DIALOG_WIDTH};
+
+type ValidateFn = Box<dyn Fn(&str) -> Result<(), String> + Send>;
+type ResultFn = Box<dyn FnOnce(DialogResult) + Send>;
+
+/// What a dialog was answered with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogResult {
+    /// An alert was closed
+    Ok,
+    Yes,
+    No,
+    /// Cancel was pressed, or escape for confirms and prompts
+    Cancel,
+    /// The text a prompt was answered with
+    Text(String),
+}
+
+enum Kind {
+    Alert,
+    Confirm,
+    Prompt,
+}
+
+/// A modal dialog, shown after the ones queued before it were answered.
+pub struct Dialog {
+    kind: Kind,
+    typ: ToastType,
+    title: Option<String>,
+    message: String,
+    /// Text of a prompt's input
+    text: String,
+    hint: String,
+    validate: Option<ValidateFn>,
+    on_result: Option<ResultFn>,
+    /// Shared with the handle
+    result: Arc<Mutex<Option<DialogResult>>>,
+}
+
+impl Dialog {
+    fn new(kind: Kind, typ: ToastType, message: String) -> Self {
+        Self {
+            kind,
+            typ,
+            title: None,
+            message,
+            text: String::new(),
+            hint: String::new(),
+            validate: None,
+            on_result: None,
+            result: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// A message with an OK button.
+    pub fn alert<S: Into<String>>(typ: ToastType, message: S) -> Self {
+        Self::new(Kind::Alert, typ, message.into())
+    }
+
+    /// A question with Yes, No and Cancel buttons.
+    pub fn confirm<S: Into<String>>(typ: ToastType, message: S) -> Self {
+        Self::new(Kind::Confirm, typ, message.into())
+    }
+
+    /// A question answered with text.
+    pub fn prompt<S: Into<String>>(typ: ToastType, message: S) -> Self {
+        Self::new(Kind::Prompt, typ, message.into())
+    }
+
+    /// Replaces the title of the dialog's type.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Text a prompt starts with.
+    pub fn text<S: Into<String>>(mut self, text: S) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// Shown in a prompt's input while it is empty.
+    pub fn hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = hint.into();
+        self
+    }
+
+    /// Checks a prompt's text as it is typed, it can't be submitted while the check returns an
+    /// error, which is shown under the input.
+    pub fn validate<F: Fn(&str) -> Result<(), String> + Send + 'static>(
+        mut self,
+        validate: F,
+    ) -> Self {
+        self.validate = Some(Box::new(validate));
+        self
+    }
+
+    /// Called with the result once the dialog is answered.
+    pub fn on_result<F: FnOnce(DialogResult) + Send + 'static>(mut self, on_result: F) -> Self {
+        self.on_result = Some(Box::new(on_result));
+        self
+    }
+
+    pub(super) fn handle(&self) -> DialogHandle {
+        DialogHandle {
+            result: self.result.clone(),
+        }
+    }
+
+    pub(super) fn get_type(&self) -> ToastType {
+        self.typ
+    }
+
+    /// Hands the result to the handle and the callback.
+    pub(super) fn finish(self, result: DialogResult) {
+        if let Ok(mut shared) = self.result.lock() {
+            *shared = Some(result.clone());
+        }
+        if let Some(on_result) = self.on_result {
+            on_result(result);
+        }
+    }
+
+    /// Draws the contents of the popup, returns the result once answered.
+    pub(super) fn draw(&mut self, ui: &Ui, theme: &ToastTheme) -> Option<DialogResult> {
+        let style = theme.style(self.typ);
+        let icon = theme.icon(self.typ);
+        let title = self.title.as_deref().or_else(|| theme.title(self.typ));
+        let _t = ui.push_text_wrap_pos_with_pos(DIALOG_WIDTH);
+        let mut result = None;
+
+        if let Some(icon) = icon {
+            icon.draw(ui, style.title_color);
+            if title.is_some() {
+                ui.same_line();
+            }
+        }
+        if let Some(title) = title {
+            ui.text_colored(style.title_color, title);
+        }
+        if icon.is_some() || title.is_some() {
+            ui.separator();
+        }
+        ui.text(&self.message);
+        ui.spacing();
+
+        let mut valid = true;
+        if let Kind::Prompt = self.kind {
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here();
+            }
+            ui.set_next_item_width(DIALOG_WIDTH - ui.cursor_pos()[0]);
+            let entered = ui
+                .input_text("##prompt", &mut self.text)
+                .hint(&self.hint)
+                .enter_returns_true(true)
+                .build();
+
+            if let Some(Err(error)) = self.validate.as_ref().map(|validate| validate(&self.text)) {
+                valid = false;
+                ui.text_colored(theme.style(ToastType::Error).title_color, error);
+            }
+            if entered && valid {
+                result = Some(DialogResult::Text(self.text.clone()));
+            }
+            ui.spacing();
+        }
+
+        let buttons: &[(&str, DialogResult)] = match self.kind {
+            Kind::Alert => &[("OK", DialogResult::Ok)],
+            Kind::Confirm => &[
+                ("Yes", DialogResult::Yes),
+                ("No", DialogResult::No),
+                ("Cancel", DialogResult::Cancel),
+            ],
+            Kind::Prompt => &[("OK", DialogResult::Ok), ("Cancel", DialogResult::Cancel)],
+        };
+        for (idx, (label, answer)) in buttons.iter().enumerate() {
+            if idx > 0 {
+                ui.same_line();
+            }
+            // Only a prompt's OK can be disabled, by validation
+            let _d = ui.begin_disabled(*answer == DialogResult::Ok && !valid);
+            if ui.button(label) {
+                result = Some(answer.clone());
+            }
+        }
+
+        if ui.is_key_pressed(Key::Escape) {
+            result = Some(match self.kind {
+                Kind::Alert => DialogResult::Ok,
+                Kind::Confirm | Kind::Prompt => DialogResult::Cancel,
+            });
+        }
+
+        // The prompt's OK button answers with the text
+        if let (Kind::Prompt, Some(DialogResult::Ok)) = (&self.kind, &result) {
+            result = Some(DialogResult::Text(self.text.clone()));
+        }
+
+        if style.accent_width > 0.0 {
+            draw_accent(ui, style.accent_width, style.rounding, style.accent_color);
+        }
+        if result.is_some() {
+            ui.close_current_popup();
+        }
+        result
+    }
+}
+
+/// Gets the result of a queued [`Dialog`].
+#[derive(Clone)]
+pub struct DialogHandle {
+    result: Arc<Mutex<Option<DialogResult>>>,
+}
+
+impl DialogHandle {
+    /// The answer, `None` while the dialog is queued or shown.
+    pub fn result(&self) -> Option<DialogResult> {
+        self.result.lock().ok().and_then(|result| result.clone())
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::{NotificationSender, Toast, ToastType};
+
+/// Which log records become toasts, shared by the `log` and `tracing` integrations.
+#[derive(Debug, Clone)]
+pub struct ForwardConfig {
+    /// Only targets starting with one of these are shown, all of them when empty
+    pub targets: Vec<String>,
+    /// Targets starting with one of these are never shown
+    pub ignored_targets: Vec<String>,
+    /// At most this many toasts are shown per `rate_period`, the rest are dropped
+    pub rate_limit: usize,
+    pub rate_period: Duration,
+    /// How long the toasts are shown in ms
+    pub duration: usize,
+}
+
+impl Default for ForwardConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            ignored_targets: Vec::new(),
+            rate_limit: 5,
+            rate_period: Duration::from_secs(10),
+            duration: 5000,
+        }
+    }
+}
+
+/// Filters and rate limits records on their way to the sender.
+pub(super) struct Forwarder {
+    sender: NotificationSender,
+    config: ForwardConfig,
+    /// When the toasts in the current rate period were sent
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl Forwarder {
+    pub(super) fn new(sender: NotificationSender) -> Self {
+        Self {
+            sender,
+            config: ForwardConfig::default(),
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub(super) fn set_config(&mut self, config: ForwardConfig) {
+        self.config = config;
+    }
+
+    pub(super) fn accepts_target(&self, target: &str) -> bool {
+        let config = &self.config;
+        (config.targets.is_empty() || config.targets.iter().any(|t| target.starts_with(t)))
+            && !config.ignored_targets.iter().any(|t| target.starts_with(t))
+    }
+
+    pub(super) fn send(&self, typ: ToastType, target: &str, message: String) {
+        if !self.take_rate() {
+            return;
+        }
+
+        // Repeats of a message are counted on the toast that is still shown
+        let key = format!("{target}\0{message}");
+        self.sender.push(
+            Toast::new(typ, self.config.duration)
+                .content(message)
+                .key(key),
+        );
+    }
+
+    fn take_rate(&self) -> bool {
+        self.take_rate_at(Instant::now())
+    }
+
+    /// Whether a toast can be sent at `now`, counting it if so.
+    fn take_rate_at(&self, now: Instant) -> bool {
+        let mut sent = match self.sent.lock() {
+            Ok(sent) => sent,
+            Err(_) => return false,
+        };
+
+        while matches!(sent.front(), Some(at) if now.duration_since(*at) > self.config.rate_period)
+        {
+            sent.pop_front();
+        }
+        if sent.len() >= self.config.rate_limit {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::Notifications;
+
+    fn forwarder(config: ForwardConfig) -> Forwarder {
+        let mut forwarder = Forwarder::new(Notifications::default().sender());
+        forwarder.set_config(config);
+        forwarder
+    }
+
+    fn targets(targets: &[&str], ignored_targets: &[&str]) -> Forwarder {
+        forwarder(ForwardConfig {
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            ignored_targets: ignored_targets.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn targets_match_by_prefix() {
+        let all = targets(&[], &[]);
+        assert!(all.accepts_target("app"));
+        assert!(all.accepts_target(""));
+
+        let some = targets(&["app::net", "db"], &["app::net::noisy"]);
+        assert!(some.accepts_target("app::net"));
+        assert!(some.accepts_target("app::net::http"));
+        assert!(some.accepts_target("db"));
+        assert!(!some.accepts_target("app"));
+        assert!(!some.accepts_target("app::ui"));
+        assert!(!some.accepts_target("app::net::noisy"));
+        assert!(!some.accepts_target("app::net::noisy::inner"));
+
+        let ignored = targets(&[], &["hyper"]);
+        assert!(ignored.accepts_target("app"));
+        assert!(!ignored.accepts_target("hyper::client"));
+    }
+
+    #[test]
+    fn rate_limit_bursts_then_refills() {
+        let forwarder = forwarder(ForwardConfig {
+            rate_limit: 3,
+            rate_period: Duration::from_secs(10),
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert!(forwarder.take_rate_at(at(0)));
+        assert!(forwarder.take_rate_at(at(1000)));
+        assert!(forwarder.take_rate_at(at(2000)));
+        assert!(!forwarder.take_rate_at(at(3000)));
+        assert!(!forwarder.take_rate_at(at(10_000)));
+
+        // The first toast leaves the period, making room for one more
+        assert!(forwarder.take_rate_at(at(10_001)));
+        assert!(!forwarder.take_rate_at(at(10_002)));
+
+        // All of them left it
+        assert!(forwarder.take_rate_at(at(30_000)));
+        assert!(forwarder.take_rate_at(at(30_000)));
+        assert!(forwarder.take_rate_at(at(30_000)));
+        assert!(!forwarder.take_rate_at(at(30_000)));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn levels_are_filtered() {
+        use crate::notify::NotificationLogger;
+        use log::{Level, LevelFilter, Log, Metadata};
+
+        let logger = NotificationLogger::new(Notifications::default().sender(), LevelFilter::Warn)
+            .config(ForwardConfig {
+                ignored_targets: vec!["noisy".to_owned()],
+                ..Default::default()
+            });
+        let enabled = |level, target| {
+            logger.enabled(&Metadata::builder().level(level).target(target).build())
+        };
+
+        assert!(enabled(Level::Error, "app"));
+        assert!(enabled(Level::Warn, "app"));
+        assert!(!enabled(Level::Info, "app"));
+        assert!(!enabled(Level::Trace, "app"));
+        assert!(!enabled(Level::Error, "noisy"));
+    }
+}
@@ -0,0 +1,177 @@
+use imgui::{Condition, TableFlags, Ui};
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+use super::{Toast, ToastTheme, ToastType};
+
+/// A toast that was shown.
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub typ: ToastType,
+    /// The toast's title, or the one of its type
+    pub title: Option<String>,
+    pub content: Option<String>,
+    /// Times the toast was pushed, when repeats were coalesced
+    pub count: usize,
+    pub time: SystemTime,
+}
+
+/// Toasts that were shown, newest last.
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    limit: usize,
+    unread: usize,
+    /// Shown types by index, custom types share the last one
+    filter: [bool; ToastType::ALL.len() + 1],
+    search: String,
+}
+
+impl History {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            limit,
+            unread: 0,
+            filter: [true; ToastType::ALL.len() + 1],
+            search: String::new(),
+        }
+    }
+
+    /// Entries past the limit are dropped oldest first, 0 keeps no history.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.truncate();
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries added since the history window was last shown, for a status bar badge.
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    pub fn mark_all_read(&mut self) {
+        self.unread = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.unread = 0;
+    }
+
+    pub(super) fn push(&mut self, toast: &Toast, theme: &ToastTheme) {
+        if self.limit == 0 {
+            return;
+        }
+        self.entries.push_back(HistoryEntry {
+            typ: toast.get_type(),
+            title: toast
+                .get_title()
+                .or_else(|| theme.title(toast.get_type()))
+                .map(str::to_owned),
+            content: toast.get_content().map(str::to_owned),
+            count: toast.get_count(),
+            time: toast.get_time(),
+        });
+        self.unread += 1;
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.entries.len() > self.limit {
+            self.entries.pop_front();
+        }
+        self.unread = self.unread.min(self.entries.len());
+    }
+
+    fn is_shown(&self, entry: &HistoryEntry) -> bool {
+        if !self.filter[entry.typ.index()] {
+            return false;
+        }
+        if self.search.is_empty() {
+            return true;
+        }
+
+        let search = self.search.to_lowercase();
+        [&entry.title, &entry.content]
+            .iter()
+            .any(|text| matches!(text, Some(text) if text.to_lowercase().contains(&search)))
+    }
+
+    /// Draws the notification center, newest entries first.
+    pub fn draw_window(&mut self, ui: &Ui, opened: &mut bool) {
+        ui.window("Notifications")
+            .size([420.0, 320.0], Condition::FirstUseEver)
+            .opened(opened)
+            .build(|| {
+                self.unread = 0;
+
+                for typ in ToastType::ALL.into_iter().chain([ToastType::Custom(0)]) {
+                    ui.checkbox(typ.desc(), &mut self.filter[typ.index()]);
+                    ui.same_line();
+                }
+                if ui.button("Clear all") {
+                    self.clear();
+                }
+                ui.set_next_item_width(-1.0);
+                ui.input_text("##search", &mut self.search)
+                    .hint("Search")
+                    .build();
+
+                let flags = TableFlags::BORDERS_INNER_H | TableFlags::ROW_BG | TableFlags::SCROLL_Y;
+                if let Some(_t) = ui.begin_table_with_flags("##history", 2, flags) {
+                    ui.table_setup_scroll_freeze(0, 1);
+                    ui.table_setup_column("Time");
+                    ui.table_setup_column("Message");
+                    ui.table_headers_row();
+
+                    for entry in self.entries.iter().rev() {
+                        if !self.is_shown(entry) {
+                            continue;
+                        }
+
+                        ui.table_next_row();
+                        ui.table_next_column();
+                        ui.text_disabled(format_age(entry.time.elapsed().unwrap_or_default()));
+
+                        ui.table_next_column();
+                        if let Some(title) = &entry.title {
+                            ui.text(title);
+                            if entry.count > 1 {
+                                ui.same_line();
+                                ui.text_disabled(format!("x{}", entry.count));
+                            }
+                        }
+                        if let Some(content) = &entry.content {
+                            ui.text_wrapped(content);
+                        }
+                    }
+                }
+            });
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        "just now".to_owned()
+    } else if secs < 60 * 60 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 60 * 60 * 24 {
+        format!("{} h ago", secs / (60 * 60))
+    } else {
+        format!("{} d ago", secs / (60 * 60 * 24))
+    }
+}
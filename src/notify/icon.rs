@@ -0,0 +1,128 @@
+use imgui::{TextureId, Ui};
+use std::f32::consts::PI;
+
+use super::with_style_alpha;
+
+/// Icon drawn in front of a toast's title.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastIcon {
+    /// Check mark
+    Success,
+    /// Exclamation mark in a triangle
+    Warning,
+    /// Cross in a circle
+    Error,
+    /// `i` in a circle
+    Info,
+    /// A glyph from a font merged into the current one, like an icon font
+    Glyph(char),
+    /// A square image, tinted with the toast's opacity
+    Texture(TextureId),
+}
+
+impl ToastIcon {
+    /// Draws the icon as a square the height of a line of text.
+    pub(super) fn draw(&self, ui: &Ui, color: [f32; 4]) {
+        if let ToastIcon::Glyph(glyph) = self {
+            ui.text_colored(color, glyph.to_string());
+            return;
+        }
+
+        let size = ui.text_line_height();
+        let min = ui.cursor_screen_pos();
+        let color = with_style_alpha(color);
+        ui.dummy([size, size]);
+
+        // Shapes are laid out in a unit square
+        let at = |x: f32, y: f32| [min[0] + x * size, min[1] + y * size];
+        let center = at(0.5, 0.5);
+        let thickness = (size * 0.12).max(1.0);
+        let draw_list = ui.get_window_draw_list();
+
+        match self {
+            ToastIcon::Success => {
+                draw_list
+                    .add_polyline(vec![at(0.15, 0.55), at(0.4, 0.8), at(0.85, 0.25)], color)
+                    .thickness(thickness)
+                    .build();
+            }
+            ToastIcon::Warning => {
+                draw_list
+                    .add_triangle(at(0.5, 0.05), at(0.95, 0.9), at(0.05, 0.9), color)
+                    .thickness(thickness)
+                    .build();
+                draw_list
+                    .add_line(at(0.5, 0.35), at(0.5, 0.6), color)
+                    .thickness(thickness)
+                    .build();
+                draw_list
+                    .add_circle(at(0.5, 0.75), thickness * 0.6, color)
+                    .filled(true)
+                    .build();
+            }
+            ToastIcon::Error => {
+                draw_list
+                    .add_circle(center, size * 0.45, color)
+                    .thickness(thickness)
+                    .build();
+                draw_list
+                    .add_line(at(0.32, 0.32), at(0.68, 0.68), color)
+                    .thickness(thickness)
+                    .build();
+                draw_list
+                    .add_line(at(0.68, 0.32), at(0.32, 0.68), color)
+                    .thickness(thickness)
+                    .build();
+            }
+            ToastIcon::Info => {
+                draw_list
+                    .add_circle(center, size * 0.45, color)
+                    .thickness(thickness)
+                    .build();
+                draw_list
+                    .add_circle(at(0.5, 0.3), thickness * 0.6, color)
+                    .filled(true)
+                    .build();
+                draw_list
+                    .add_line(at(0.5, 0.45), at(0.5, 0.75), color)
+                    .thickness(thickness)
+                    .build();
+            }
+            ToastIcon::Texture(texture_id) => {
+                draw_list
+                    .add_image(*texture_id, min, at(1.0, 1.0))
+                    .col([1.0, 1.0, 1.0, color[3]])
+                    .build();
+            }
+            ToastIcon::Glyph(_) => unreachable!(),
+        }
+    }
+}
+
+/// Draws a spinning arc the size of an icon.
+pub(super) fn draw_spinner(ui: &Ui, color: [f32; 4]) {
+    const SEGMENTS: usize = 16;
+
+    let size = ui.text_line_height();
+    let min = ui.cursor_screen_pos();
+    let color = with_style_alpha(color);
+    ui.dummy([size, size]);
+
+    let center = [min[0] + size * 0.5, min[1] + size * 0.5];
+    let radius = size * 0.4;
+    let start = ui.time() as f32 * PI * 2.0;
+    let points = (0..=SEGMENTS)
+        .map(|i| {
+            let angle = start + i as f32 / SEGMENTS as f32 * PI * 1.5;
+            [
+                center[0] + angle.cos() * radius,
+                center[1] + angle.sin() * radius,
+            ]
+        })
+        .collect();
+
+    ui.get_window_draw_list()
+        .add_polyline(points, color)
+        .thickness((size * 0.12).max(1.0))
+        .build();
+}
@@ -0,0 +1,79 @@
+use std::fmt::{self, Write};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+use super::{
+    forward::{ForwardConfig, Forwarder},
+    NotificationSender, ToastType,
+};
+
+/// A [`tracing_subscriber`] layer showing events at or above a level as toasts.
+pub struct NotificationLayer {
+    level: Level,
+    forwarder: Forwarder,
+}
+
+impl NotificationLayer {
+    pub fn new(sender: NotificationSender, level: Level) -> Self {
+        Self {
+            level,
+            forwarder: Forwarder::new(sender),
+        }
+    }
+
+    pub fn config(mut self, config: ForwardConfig) -> Self {
+        self.forwarder.set_config(config);
+        self
+    }
+}
+
+impl<S: Subscriber> Layer<S> for NotificationLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // Levels compare by verbosity, errors are the smallest
+        if *metadata.level() > self.level || !self.forwarder.accepts_target(metadata.target()) {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut message = visitor.message;
+        message.push_str(&visitor.fields);
+
+        let typ = match *metadata.level() {
+            Level::ERROR => ToastType::Error,
+            Level::WARN => ToastType::Warning,
+            Level::INFO => ToastType::Info,
+            _ => ToastType::None,
+        };
+        self.forwarder.send(typ, metadata.target(), message);
+    }
+}
+
+/// Collects the message of an event followed by its other fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
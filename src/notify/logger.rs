@@ -0,0 +1,57 @@
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use super::{
+    forward::{ForwardConfig, Forwarder},
+    NotificationSender, ToastType,
+};
+
+/// A [`log`] logger showing records at or above a level as toasts.
+pub struct NotificationLogger {
+    level: LevelFilter,
+    forwarder: Forwarder,
+}
+
+impl NotificationLogger {
+    pub fn new(sender: NotificationSender, level: LevelFilter) -> Self {
+        Self {
+            level,
+            forwarder: Forwarder::new(sender),
+        }
+    }
+
+    pub fn config(mut self, config: ForwardConfig) -> Self {
+        self.forwarder.set_config(config);
+        self
+    }
+
+    /// Sets this as the global logger.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let level = self.level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for NotificationLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && self.forwarder.accepts_target(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let typ = match record.level() {
+            Level::Error => ToastType::Error,
+            Level::Warn => ToastType::Warning,
+            Level::Info => ToastType::Info,
+            Level::Debug | Level::Trace => ToastType::None,
+        };
+        self.forwarder
+            .send(typ, record.target(), record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
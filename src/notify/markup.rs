@@ -0,0 +1,246 @@
+use imgui::{MouseCursor, StyleColor, Ui};
+
+use super::with_style_alpha;
+
+/// A run of text drawn the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Span {
+    pub(super) text: String,
+    pub(super) bold: bool,
+    pub(super) color: Option<[f32; 4]>,
+    /// Reported when clicked
+    pub(super) link: Option<String>,
+}
+
+pub(super) type Paragraph = Vec<Span>;
+
+/// Splits `markup` into paragraphs, also returns its text without the markup.
+pub(super) fn parse(markup: &str) -> (Vec<Paragraph>, String) {
+    let mut paragraphs = Vec::new();
+    let mut lines = Vec::new();
+    for line in markup.lines().chain([""]) {
+        if !line.trim().is_empty() {
+            lines.push(line);
+        } else if !lines.is_empty() {
+            paragraphs.push(parse_paragraph(&lines.join("\n")));
+            lines.clear();
+        }
+    }
+
+    let plain = paragraphs
+        .iter()
+        .map(|spans| {
+            spans
+                .iter()
+                .map(|span| span.text.as_str())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (paragraphs, plain)
+}
+
+fn parse_paragraph(paragraph: &str) -> Paragraph {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut bold = false;
+    let mut color = None;
+
+    let mut rest = paragraph;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            push_span(&mut spans, &mut text, bold, color, None);
+            bold = !bold;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{/}") {
+            push_span(&mut spans, &mut text, bold, color, None);
+            color = None;
+            rest = after;
+        } else if let Some((new_color, after)) = parse_color(rest) {
+            push_span(&mut spans, &mut text, bold, color, None);
+            color = Some(new_color);
+            rest = after;
+        } else if let Some((label, link, after)) = parse_link(rest) {
+            push_span(&mut spans, &mut text, bold, color, None);
+            text.push_str(label);
+            push_span(&mut spans, &mut text, bold, color, Some(link));
+            rest = after;
+        } else if let Some(escaped) = rest
+            .strip_prefix('\\')
+            .and_then(|after| after.chars().next())
+        {
+            text.push(escaped);
+            rest = &rest[1 + escaped.len_utf8()..];
+        } else {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    push_span(&mut spans, &mut text, bold, color, None);
+
+    spans
+}
+
+/// Ends the span of `text` so far, if there is any.
+fn push_span(
+    spans: &mut Paragraph,
+    text: &mut String,
+    bold: bool,
+    color: Option<[f32; 4]>,
+    link: Option<&str>,
+) {
+    if !text.is_empty() {
+        spans.push(Span {
+            text: std::mem::take(text),
+            bold,
+            color,
+            link: link.map(str::to_owned),
+        });
+    }
+}
+
+/// Parses `{#rrggbb}` or `{#rrggbbaa}` at the start of `s`.
+fn parse_color(s: &str) -> Option<([f32; 4], &str)> {
+    let after = s.strip_prefix("{#")?;
+    let end = after.find('}')?;
+    let hex = &after[..end];
+    if !matches!(hex.len(), 6 | 8) {
+        return None;
+    }
+
+    let mut color = [1.0; 4];
+    for (idx, channel) in color.iter_mut().enumerate().take(hex.len() / 2) {
+        let byte = u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+        *channel = byte as f32 / 255.0;
+    }
+    Some((color, &after[end + 1..]))
+}
+
+/// Parses `[label](url)` at the start of `s`.
+fn parse_link(s: &str) -> Option<(&str, &str, &str)> {
+    let after = s.strip_prefix('[')?;
+    let label_end = after.find("](")?;
+    let label = &after[..label_end];
+    let after = &after[label_end + 2..];
+    let url_end = after.find(')')?;
+    if label.is_empty() || label.contains('\n') {
+        return None;
+    }
+    Some((label, &after[..url_end], &after[url_end + 1..]))
+}
+
+/// Draws the paragraphs wrapped at `wrap_pos`, returns the link that was clicked.
+pub(super) fn draw(
+    ui: &Ui,
+    paragraphs: &[Paragraph],
+    wrap_pos: f32,
+    link_color: [f32; 4],
+) -> Option<String> {
+    let mut clicked = None;
+    let text_color = ui.style_color(StyleColor::Text);
+
+    for (idx, spans) in paragraphs.iter().enumerate() {
+        if idx > 0 {
+            ui.spacing();
+        }
+
+        // Spans are laid out word by word, so the words of a line can be styled differently
+        let mut line_start = true;
+        for span in spans {
+            let color = match (&span.link, span.color) {
+                (Some(_), _) => link_color,
+                (None, Some(color)) => color,
+                (None, None) => text_color,
+            };
+
+            for word in span.text.split_inclusive([' ', '\n']) {
+                let text = word.trim_end_matches('\n');
+                let width = ui.calc_text_size(text.trim_end())[0];
+                let line_end = ui.item_rect_max()[0] - ui.window_pos()[0];
+                if !line_start && line_end + width <= wrap_pos {
+                    ui.same_line_with_spacing(0.0, 0.0);
+                }
+
+                let min = ui.cursor_screen_pos();
+                ui.text_colored(color, text);
+                let draw_list = ui.get_window_draw_list();
+                if span.bold {
+                    draw_list.add_text([min[0] + 1.0, min[1]], with_style_alpha(color), text);
+                }
+                if let Some(link) = &span.link {
+                    if ui.is_item_hovered() {
+                        ui.set_mouse_cursor(Some(MouseCursor::Hand));
+                        let max = ui.item_rect_max();
+                        draw_list
+                            .add_line([min[0], max[1]], max, with_style_alpha(color))
+                            .build();
+                    }
+                    if ui.is_item_clicked() {
+                        clicked = Some(link.clone());
+                    }
+                }
+
+                line_start = word.ends_with('\n');
+            }
+        }
+    }
+
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str) -> Span {
+        Span {
+            text: text.to_owned(),
+            bold: false,
+            color: None,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn paragraphs_are_split_on_blank_lines() {
+        let (paragraphs, plain) = parse("one\ntwo\n\n  \nthree\n");
+        assert_eq!(
+            paragraphs,
+            vec![vec![span("one\ntwo")], vec![span("three")]]
+        );
+        assert_eq!(plain, "one\ntwo\n\nthree");
+    }
+
+    #[test]
+    fn spans_are_styled() {
+        let (paragraphs, plain) = parse("a **b {#ff0000}c{/}** [d](https://e) \\**f");
+        assert_eq!(
+            paragraphs[0],
+            vec![
+                span("a "),
+                Span {
+                    bold: true,
+                    ..span("b ")
+                },
+                Span {
+                    bold: true,
+                    color: Some([1.0, 0.0, 0.0, 1.0]),
+                    ..span("c")
+                },
+                span(" "),
+                Span {
+                    link: Some("https://e".to_owned()),
+                    ..span("d")
+                },
+                span(" **f"),
+            ]
+        );
+        assert_eq!(plain, "a b c d **f");
+    }
+
+    #[test]
+    fn malformed_markup_is_text() {
+        let (paragraphs, _) = parse("{#12} [no link] {#gggggg}");
+        assert_eq!(paragraphs[0], vec![span("{#12} [no link] {#gggggg}")]);
+    }
+}
@@ -1,111 +1,696 @@
 use imgui::{
-    sys::{igGetCursorPosY, igGetWindowHeight, igSetCursorPosY},
-    Condition, StyleColor, StyleVar, Ui, WindowFlags,
+    sys::{
+        igGetCursorPosY, igGetStyle, igGetWindowHeight, igSetCursorPosY, igSetNextWindowPos,
+        igSetNextWindowViewport, ImGuiCond_Appearing, ImVec2,
+    },
+    Condition, Image, MouseButton, ProgressBar, StyleColor, StyleVar, Ui, WindowFlags,
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::Duration,
 };
 
+mod anim;
+mod clock;
+mod config;
+mod dialog;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod forward;
+mod history;
+mod icon;
+#[cfg(feature = "tracing")]
+mod layer;
+#[cfg(feature = "log")]
+mod logger;
+mod markup;
+mod sender;
+mod style;
 mod toast;
 
+pub use anim::{Animation, Easing};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::{Anchor, NotificationsConfig, StackDirection};
+pub use dialog::{Dialog, DialogHandle, DialogResult};
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub use forward::ForwardConfig;
+pub use history::{History, HistoryEntry};
+pub use icon::ToastIcon;
+#[cfg(feature = "tracing")]
+pub use layer::NotificationLayer;
+#[cfg(feature = "log")]
+pub use logger::NotificationLogger;
+use sender::{Command, Update};
+pub use sender::{NotificationSender, ToastHandle};
+pub use style::{ToastStyle, ToastTheme};
 pub use toast::*;
 
 const PADDING_X: f32 = 20.0;
 const PADDING_Y: f32 = 20.0;
 const PADDING_MESSAGE_Y: f32 = 10.0;
 const FADE_IN_OUT_TIME: usize = 150;
-const OPACITY: f32 = 1.0;
+const PROGRESS_WIDTH: f32 = 200.0;
+const HISTORY_LIMIT: usize = 100;
+const DIALOG_WIDTH: f32 = 400.0;
+/// Fastest the timers can run, enough to skip through any toast in a frame
+const MAX_SPEED: f32 = 1000.0;
+
+pub struct Notifications {
+    toasts: Vec<Toast>,
+    /// Toasts waiting for a free spot when `max_visible` is reached
+    queue: VecDeque<Toast>,
+    config: NotificationsConfig,
+    history: History,
+    /// Shared with the senders, so they can hand out ids
+    next_id: Arc<AtomicU64>,
+    tx: Sender<Command>,
+    rx: Receiver<Command>,
+    clock: Box<dyn Clock>,
+    /// Clock time of the last update
+    last_time: Duration,
+    paused: bool,
+    speed: f32,
+    /// Dialogs waiting to be answered, the first one is shown
+    dialogs: VecDeque<Dialog>,
+    /// Whether the popup of the first dialog was opened
+    dialog_open: bool,
+}
 
-#[derive(Default)]
-pub struct Notifications(Vec<Toast>);
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::with_config(NotificationsConfig::default())
+    }
+}
 
 impl Notifications {
-    pub fn push(&mut self, toast: Toast) {
-        self.0.push(toast);
+    pub fn with_config(config: NotificationsConfig) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            toasts: Vec::new(),
+            queue: VecDeque::new(),
+            config,
+            history: History::new(HISTORY_LIMIT),
+            next_id: Arc::new(AtomicU64::new(0)),
+            tx,
+            rx,
+            clock: Box::new(SystemClock::new()),
+            last_time: Duration::ZERO,
+            paused: false,
+            speed: 1.0,
+            dialogs: VecDeque::new(),
+            dialog_open: false,
+        }
     }
 
-    pub fn render(&mut self, ui: &Ui) {
-        let vp_size = unsafe { (*imgui::sys::igGetMainViewport()).Size };
-        let mut height = 0.0;
-        let mut idx = 0;
+    /// Replaces the clock toasts are timed by.
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.last_time = clock.now();
+        self.clock = Box::new(clock);
+    }
 
-        let _sv = ui.push_style_var(StyleVar::WindowRounding(5.0));
-        let _ct = ui.push_style_color(
-            StyleColor::WindowBg,
-            [43. / 255., 43. / 255., 43. / 255., 100. / 255.],
-        );
+    /// Stops the timers of all toasts, like while the app is paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// How fast the timers of toasts run, 1.0 is the speed of the clock. Clamped to
+    /// `0.0..=1000.0`, NaN stops them.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = if speed.is_nan() {
+            0.0
+        } else {
+            speed.clamp(0.0, MAX_SPEED)
+        };
+    }
+
+    /// A sender for pushing toasts from other threads.
+    pub fn sender(&self) -> NotificationSender {
+        NotificationSender::new(self.tx.clone(), self.next_id.clone())
+    }
+
+    pub fn config(&mut self) -> &mut NotificationsConfig {
+        &mut self.config
+    }
+
+    /// Shows a toast, or counts it on a shown toast it is a repeat of. Returns the id of the
+    /// toast it is shown as.
+    pub fn push(&mut self, mut toast: Toast) -> ToastId {
+        toast.set_id(ToastId(self.next_id.fetch_add(1, Ordering::Relaxed)));
+        self.insert(toast)
+    }
+
+    fn insert(&mut self, toast: Toast) -> ToastId {
+        let coalesce = self.config.coalesce;
+        if let Some(same) = self
+            .toasts
+            .iter_mut()
+            .filter(|same| same.get_phase() != ToastPhase::Expired)
+            .chain(self.queue.iter_mut())
+            .find(|same| same.is_same(&toast, coalesce))
+        {
+            same.repeat(toast);
+            return same.get_id();
+        }
+
+        let id = toast.get_id();
+        if self.is_full() {
+            self.queue.push_back(toast);
+        } else {
+            self.toasts.push(toast);
+        }
+        id
+    }
+
+    /// A live toast, to update it while it is shown or queued.
+    pub fn get_mut(&mut self, id: ToastId) -> Option<&mut Toast> {
+        self.toasts
+            .iter_mut()
+            .chain(self.queue.iter_mut())
+            .find(|toast| toast.get_id() == id)
+    }
+
+    /// Starts fading out a shown or queued toast, returns whether it was found.
+    pub fn dismiss(&mut self, id: ToastId) -> bool {
+        match self.get_mut(id) {
+            Some(toast) => {
+                toast.dismiss();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Toasts that were shown, kept after they expire.
+    pub fn history(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Number of toasts waiting to be shown.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Queues a modal dialog, it is shown once the ones before it are answered.
+    pub fn dialog(&mut self, dialog: Dialog) -> DialogHandle {
+        let handle = dialog.handle();
+        self.dialogs.push_back(dialog);
+        handle
+    }
+
+    /// Applies what was sent from other threads.
+    fn receive(&mut self) {
+        while let Ok(command) = self.rx.try_recv() {
+            match command {
+                Command::Push(toast) => {
+                    self.insert(*toast);
+                }
+                Command::Update(id, update) => {
+                    let toast = match self.get_mut(id) {
+                        Some(toast) => toast,
+                        None => continue,
+                    };
+                    match update {
+                        Update::Title(title) => toast.set_title(title),
+                        Update::Content(content) => toast.set_content(content),
+                        Update::Type(typ) => toast.set_type(typ),
+                        Update::Progress(progress) => toast.set_progress(progress),
+                        Update::Complete(typ) => toast.complete(typ),
+                        Update::Dismiss => toast.dismiss(),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Advances the timers by the clock, moves expired toasts to the history and shows queued
+    /// toasts in their place.
+    fn update(&mut self) {
+        self.receive();
 
-        self.0.retain(|toast| {
-            if toast.get_phase() == ToastPhase::Expired {
-                return false;
+        let now = self.clock.now();
+        let delta = now.saturating_sub(self.last_time);
+        self.last_time = now;
+        if !self.paused {
+            let delta = Duration::try_from_secs_f64(delta.as_secs_f64() * self.speed as f64)
+                .unwrap_or(Duration::MAX);
+            for toast in &mut self.toasts {
+                toast.tick(delta);
             }
+        }
 
-            let icon = toast.get_icon();
-            let title = toast.get_title();
+        let history = &mut self.history;
+        let theme = &self.config.theme;
+        self.toasts.retain(|toast| {
+            let expired = toast.get_phase() == ToastPhase::Expired;
+            if expired {
+                history.push(toast, theme);
+            }
+            !expired
+        });
+        while !self.is_full() {
+            match self.queue.pop_front() {
+                Some(mut toast) => {
+                    toast.restart();
+                    self.toasts.push(toast);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        matches!(self.config.max_visible, Some(max) if self.toasts.len() >= max)
+    }
+
+    /// Draws the toasts, returns what the user did with them this frame.
+    pub fn render(&mut self, ui: &Ui) -> Vec<ToastEvent> {
+        let layout = self.config.layout();
+        let spacing = self.config.spacing;
+        let pause_on_hover = self.config.pause_on_hover;
+        let smooth_stacking = self.config.smooth_stacking;
+        let margin = self.config.margin;
+        let delta_time = ui.io().delta_time;
+        let mut height = 0.0;
+        let mut events = Vec::new();
+
+        self.update();
+
+        let theme = &self.config.theme;
+        for (slot, toast) in self.toasts.iter_mut().enumerate() {
+            let id = toast.get_id();
+            let typ = toast.get_type();
+            let offset = toast.move_to(height, delta_time, smooth_stacking);
+            let icon = toast.get_icon(theme.icon(typ));
+            let title = toast.get_title().or_else(|| theme.title(typ));
             let content = toast.get_content();
-            let default_title = toast.get_default_title();
-            let opacity = toast.get_fade_percent();
+            let paragraphs = toast.get_paragraphs();
+            let blocks = toast.get_blocks();
+            let has_content = content.is_some() || !blocks.is_empty();
+            let frame = toast.get_frame();
+            let progress = toast.get_progress();
+            let count = toast.get_count();
+
+            let style = theme.style(typ);
+            let text_color = style.title_color;
+
+            // Slides are the toast's size and the margin long, to be just out of the viewport.
+            // The size isn't known before the first frame, which is drawn invisible to measure it
+            let size = toast.get_size();
+            let [slide_x, slide_y] = size.unwrap_or_default();
+            let slide = [
+                layout.slide[0] * frame.slide * (slide_x + margin[0]),
+                layout.slide[1] * frame.slide * (slide_y + margin[1]),
+            ];
+            let mut window_size = None;
 
-            let mut text_color = toast.get_color();
-            text_color[3] = opacity;
+            let _sv = [
+                ui.push_style_var(StyleVar::Alpha(size.map_or(0.0, |_| frame.alpha))),
+                ui.push_style_var(StyleVar::WindowRounding(style.rounding)),
+                ui.push_style_var(StyleVar::WindowBorderSize(style.border_size)),
+                ui.push_style_var(StyleVar::WindowPadding(style.padding)),
+            ];
+            let _ct = [
+                ui.push_style_color(StyleColor::WindowBg, style.background),
+                ui.push_style_color(StyleColor::Border, style.border_color),
+            ];
 
-            let window_name = format!("##TOAST{idx}");
+            // Named by place in the stack, so imgui's windows are reused as toasts come and go
+            let window_name = format!("##TOAST{slot}");
+            let mut hovered = false;
+            let mut clicked = false;
+            let mut closed = false;
+            let mut action = None;
+            let mut link = None;
+
+            // Toasts with nothing to click let clicks through to the windows below
+            let mut flags = WindowFlags::ALWAYS_AUTO_RESIZE
+                | WindowFlags::NO_DECORATION
+                | WindowFlags::NO_MOVE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS
+                | WindowFlags::NO_FOCUS_ON_APPEARING;
+            if !pause_on_hover && !toast.is_interactive() {
+                flags |= WindowFlags::NO_INPUTS;
+            }
 
+            unsafe { igSetNextWindowViewport(layout.viewport_id) };
             ui.window(window_name)
-                .bg_alpha(opacity)
                 .position(
-                    [vp_size.x - PADDING_X, vp_size.y - PADDING_Y - height],
+                    [
+                        layout.origin[0] + slide[0],
+                        layout.origin[1] + offset * layout.step + slide[1],
+                    ],
                     Condition::Always,
                 )
-                .position_pivot([1.0, 1.0])
-                .flags(
-                    WindowFlags::ALWAYS_AUTO_RESIZE
-                        | WindowFlags::NO_DECORATION
-                        | WindowFlags::NO_INPUTS
-                        | WindowFlags::NO_NAV
-                        | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS
-                        | WindowFlags::NO_FOCUS_ON_APPEARING,
-                )
+                .position_pivot(layout.pivot)
+                .flags(flags)
                 .build(|| {
-                    let _t = ui.push_text_wrap_pos_with_pos(vp_size.x / 3.0);
+                    if frame.scale != 1.0 {
+                        ui.set_window_font_scale(frame.scale);
+                    }
+                    let _t = ui.push_text_wrap_pos_with_pos(layout.max_width);
                     let mut was_title_rendered = false;
 
-                    if let Some(_icon) = icon {
-                        todo!()
-                    }
+                    // The spinner takes the place of the icon while the job runs
+                    let has_icon = if progress == Some(Progress::Indeterminate) {
+                        icon::draw_spinner(ui, text_color);
+                        true
+                    } else if let Some(icon) = icon {
+                        icon.draw(ui, text_color);
+                        true
+                    } else {
+                        false
+                    };
 
                     if let Some(title) = title {
-                        if icon.is_some() {
+                        if has_icon {
                             ui.same_line();
                         }
 
-                        ui.text(title);
+                        ui.text_colored(text_color, title);
                         was_title_rendered = true;
-                    } else if let Some(title) = default_title {
-                        if icon.is_some() {
+                    }
+
+                    if count > 1 {
+                        if has_icon || was_title_rendered {
                             ui.same_line();
                         }
+                        draw_badge(ui, &format!("x{count}"), text_color);
+                    }
 
-                        ui.text(title);
-                        was_title_rendered = true;
+                    if toast.is_closable() {
+                        if has_icon || was_title_rendered {
+                            ui.same_line();
+                        }
+                        closed = ui.small_button("x");
                     }
 
-                    if was_title_rendered && content.is_some() {
+                    if was_title_rendered && has_content {
                         unsafe {
                             igSetCursorPosY(igGetCursorPosY() + 5.0);
                         }
+                        ui.separator();
                     }
 
-                    if let Some(content) = content {
-                        if was_title_rendered {
-                            ui.separator();
-                        }
+                    if let Some(paragraphs) = paragraphs {
+                        link = markup::draw(ui, paragraphs, layout.max_width, style.link_color);
+                    } else if let Some(content) = content {
                         ui.text(content);
                     }
 
-                    height += unsafe { igGetWindowHeight() } + PADDING_MESSAGE_Y;
+                    for block in blocks {
+                        match block {
+                            Block::Image(texture_id, size) => {
+                                Image::new(*texture_id, *size).build(ui);
+                            }
+                            Block::Custom(draw) => draw(ui),
+                        }
+                    }
+
+                    if let Some(Progress::Determinate(fraction)) = progress {
+                        ProgressBar::new(fraction.clamp(0.0, 1.0))
+                            .size([PROGRESS_WIDTH, 0.0])
+                            .build(ui);
+                    }
+
+                    let actions = toast.get_actions();
+                    if !actions.is_empty() {
+                        unsafe {
+                            igSetCursorPosY(igGetCursorPosY() + 5.0);
+                        }
+                        for (idx, (label, action_id)) in actions.iter().enumerate() {
+                            let _id = ui.push_id_usize(idx);
+                            if idx > 0 {
+                                ui.same_line();
+                            }
+                            if ui.button(label) {
+                                action = Some(*action_id);
+                            }
+                        }
+                    }
+
+                    hovered = ui.is_window_hovered();
+                    clicked = hovered
+                        && !ui.is_any_item_hovered()
+                        && ui.is_mouse_clicked(MouseButton::Left);
+
+                    window_size = Some(ui.window_size());
+                    height += unsafe { igGetWindowHeight() } + spacing;
+
+                    if style.accent_width > 0.0 {
+                        draw_accent(ui, style.accent_width, style.rounding, style.accent_color);
+                    }
                 });
+            if let Some(window_size) = window_size {
+                toast.set_size(window_size);
+            }
 
-            idx += 1;
+            if pause_on_hover {
+                toast.set_paused(hovered);
+            }
+            if let Some(action) = action {
+                toast.dismiss();
+                events.push(ToastEvent::Action(id, action));
+            } else if closed {
+                toast.dismiss();
+                events.push(ToastEvent::Closed(id));
+            } else if let Some(link) = link {
+                events.push(ToastEvent::Link(id, link));
+            } else if clicked {
+                if toast.is_dismissed_on_click() {
+                    toast.dismiss();
+                }
+                events.push(ToastEvent::Clicked(id));
+            }
+        }
 
-            true
-        });
+        if !self.queue.is_empty() {
+            unsafe { igSetNextWindowViewport(layout.viewport_id) };
+            ui.window("##TOAST_MORE")
+                .position(
+                    [layout.origin[0], layout.origin[1] + height * layout.step],
+                    Condition::Always,
+                )
+                .position_pivot(layout.pivot)
+                .flags(
+                    WindowFlags::ALWAYS_AUTO_RESIZE
+                        | WindowFlags::NO_DECORATION
+                        | WindowFlags::NO_INPUTS
+                        | WindowFlags::NO_SAVED_SETTINGS
+                        | WindowFlags::NO_NAV
+                        | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS
+                        | WindowFlags::NO_FOCUS_ON_APPEARING,
+                )
+                .build(|| {
+                    ui.text_disabled(format!("+{} more", self.queue.len()));
+                });
+        }
+
+        self.draw_dialog(ui, layout.center, layout.viewport_id);
+
+        events
+    }
+
+    /// Draws the first queued dialog, centered on the viewport.
+    fn draw_dialog(&mut self, ui: &Ui, center: [f32; 2], viewport_id: u32) {
+        let dialog = match self.dialogs.front_mut() {
+            Some(dialog) => dialog,
+            None => return,
+        };
+        let style = self.config.theme.style(dialog.get_type());
+
+        // A popup is opened once and stays open until it is closed
+        if !self.dialog_open {
+            ui.open_popup("##DIALOG");
+            self.dialog_open = true;
+        }
+
+        let _sv = [
+            ui.push_style_var(StyleVar::WindowRounding(style.rounding)),
+            ui.push_style_var(StyleVar::WindowBorderSize(style.border_size)),
+            ui.push_style_var(StyleVar::WindowPadding(style.padding)),
+        ];
+        let _ct = [
+            ui.push_style_color(StyleColor::PopupBg, style.background),
+            ui.push_style_color(StyleColor::Border, style.border_color),
+        ];
+        unsafe {
+            igSetNextWindowViewport(viewport_id);
+            igSetNextWindowPos(
+                ImVec2::new(center[0], center[1]),
+                ImGuiCond_Appearing as i32,
+                ImVec2::new(0.5, 0.5),
+            );
+        }
+
+        let theme = &self.config.theme;
+        let result = ui
+            .modal_popup_config("##DIALOG")
+            .title_bar(false)
+            .movable(false)
+            .always_auto_resize(true)
+            .build(|| dialog.draw(ui, theme));
+
+        match result {
+            Some(Some(result)) => {
+                if let Some(dialog) = self.dialogs.pop_front() {
+                    dialog.finish(result);
+                }
+                self.dialog_open = false;
+            }
+            Some(None) => {}
+            // Closed by something else, it is opened again
+            None => self.dialog_open = false,
+        }
+    }
+}
+
+/// `color` faded by the style's alpha, which draw lists don't apply like widgets do.
+fn with_style_alpha(color: [f32; 4]) -> [f32; 4] {
+    let alpha = unsafe { (*igGetStyle()).Alpha };
+    [color[0], color[1], color[2], color[3] * alpha]
+}
+
+/// Draws a bar of `color` along the left edge of the window, rounded like its corners.
+fn draw_accent(ui: &Ui, width: f32, rounding: f32, color: [f32; 4]) {
+    let min = ui.window_pos();
+    let max = [min[0] + width.max(rounding), min[1] + ui.window_size()[1]];
+
+    ui.get_window_draw_list()
+        .add_rect(min, max, with_style_alpha(color))
+        .filled(true)
+        .rounding(rounding)
+        .round_top_right(false)
+        .round_bot_right(false)
+        .build();
+}
+
+/// Draws `text` on a rounded background of `color`.
+fn draw_badge(ui: &Ui, text: &str, color: [f32; 4]) {
+    let size = ui.calc_text_size(text);
+    let padding = ui.text_line_height() * 0.25;
+    let min = ui.cursor_screen_pos();
+    let max = [min[0] + size[0] + padding * 2.0, min[1] + size[1]];
+
+    ui.get_window_draw_list()
+        .add_rect(min, max, with_style_alpha(color))
+        .filled(true)
+        .rounding(size[1] * 0.5)
+        .build();
+    ui.set_cursor_screen_pos([min[0] + padding, min[1]]);
+    ui.text_colored([0.0, 0.0, 0.0, color[3]], text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Notifications timed by the returned clock, showing a toast of 1000ms between 100ms fades.
+    fn notifications() -> (Notifications, ManualClock) {
+        let clock = ManualClock::new();
+        let mut notifications = Notifications::default();
+        notifications.set_clock(clock.clone());
+        notifications.push(Toast::new(ToastType::Info, 1000).animation_time(100, 100));
+        (notifications, clock)
+    }
+
+    fn phase(notifications: &Notifications) -> Option<ToastPhase> {
+        notifications.toasts.first().map(Toast::get_phase)
+    }
+
+    #[test]
+    fn update_follows_the_clock() {
+        let (mut notifications, clock) = notifications();
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeIn));
+
+        clock.advance(ms(150));
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::Wait));
+
+        notifications.set_paused(true);
+        clock.advance(ms(5000));
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::Wait));
+
+        // 150ms + 2 * 500ms puts the toast 50ms into its fade out
+        notifications.set_paused(false);
+        notifications.set_speed(2.0);
+        clock.advance(ms(500));
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeOut));
+
+        clock.advance(ms(30));
+        notifications.update();
+        assert_eq!(phase(&notifications), None);
+        assert_eq!(notifications.history().len(), 1);
+    }
+
+    #[test]
+    fn actions_dismiss_hovered_toasts() {
+        let clock = ManualClock::new();
+        let mut notifications = Notifications::default();
+        notifications.set_clock(clock.clone());
+        notifications.push(
+            Toast::new(ToastType::Info, 1000)
+                .animation_time(100, 100)
+                .action("Undo", 0),
+        );
+        clock.advance(ms(500));
+        notifications.update();
+
+        // What render does for a click on the action, the cursor still over the toast after
+        let toast = &mut notifications.toasts[0];
+        toast.set_paused(true);
+        toast.dismiss();
+        toast.set_paused(true);
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeOut));
+
+        clock.advance(ms(101));
+        notifications.update();
+        assert_eq!(phase(&notifications), None);
+    }
+
+    #[test]
+    fn toasts_are_dismissed_by_id() {
+        let (mut notifications, clock) = notifications();
+        let id = notifications.toasts[0].get_id();
+        clock.advance(ms(500));
+        notifications.update();
+
+        assert!(notifications.dismiss(id));
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeOut));
+        assert!(!notifications.dismiss(ToastId(id.0 + 1)));
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let (mut notifications, clock) = notifications();
+        notifications.set_speed(f32::NAN);
+        clock.advance(ms(5000));
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeIn));
+
+        notifications.set_speed(-1.0);
+        clock.advance(ms(5000));
+        notifications.update();
+        assert_eq!(phase(&notifications), Some(ToastPhase::FadeIn));
+
+        notifications.set_speed(f32::INFINITY);
+        clock.advance(ms(2));
+        notifications.update();
+        assert_eq!(phase(&notifications), None);
+
+        clock.advance(Duration::from_nanos(u64::MAX / 2));
+        notifications.update();
     }
 }
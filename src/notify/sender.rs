@@ -0,0 +1,99 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::Sender,
+    Arc, Mutex,
+};
+
+use super::{Progress, Toast, ToastId, ToastType};
+
+pub(super) enum Command {
+    Push(Box<Toast>),
+    Update(ToastId, Update),
+}
+
+pub(super) enum Update {
+    Title(String),
+    Content(String),
+    Type(ToastType),
+    Progress(Progress),
+    Complete(ToastType),
+    Dismiss,
+}
+
+/// Pushes toasts from other threads, they are shown on the next
+/// [`Notifications::render`](super::Notifications::render).
+#[derive(Clone)]
+pub struct NotificationSender {
+    tx: Arc<Mutex<Sender<Command>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl NotificationSender {
+    pub(super) fn new(tx: Sender<Command>, next_id: Arc<AtomicU64>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            next_id,
+        }
+    }
+
+    /// Queues a toast. Updates through the handle are dropped once the toast is gone, or when it
+    /// was coalesced into another toast.
+    pub fn push(&self, mut toast: Toast) -> ToastHandle {
+        let id = ToastId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        toast.set_id(id);
+        self.send(Command::Push(Box::new(toast)));
+        ToastHandle {
+            id,
+            sender: self.clone(),
+        }
+    }
+
+    fn send(&self, command: Command) {
+        // Fails only when the notifications were dropped, there is nobody to show toasts to
+        if let Ok(tx) = self.tx.lock() {
+            let _ = tx.send(command);
+        }
+    }
+}
+
+/// Updates a toast pushed through a [`NotificationSender`].
+#[derive(Clone)]
+pub struct ToastHandle {
+    id: ToastId,
+    sender: NotificationSender,
+}
+
+impl ToastHandle {
+    pub fn id(&self) -> ToastId {
+        self.id
+    }
+
+    pub fn set_title<S: Into<String>>(&self, title: S) {
+        self.update(Update::Title(title.into()));
+    }
+
+    pub fn set_content<S: Into<String>>(&self, content: S) {
+        self.update(Update::Content(content.into()));
+    }
+
+    pub fn set_type(&self, typ: ToastType) {
+        self.update(Update::Type(typ));
+    }
+
+    pub fn set_progress(&self, progress: Progress) {
+        self.update(Update::Progress(progress));
+    }
+
+    /// See [`Toast::complete`].
+    pub fn complete(&self, typ: ToastType) {
+        self.update(Update::Complete(typ));
+    }
+
+    pub fn dismiss(&self) {
+        self.update(Update::Dismiss);
+    }
+
+    fn update(&self, update: Update) {
+        self.sender.send(Command::Update(self.id, update));
+    }
+}
@@ -0,0 +1,159 @@
+use super::{ToastIcon, ToastType};
+
+/// How toasts of a type look.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToastStyle {
+    /// Color of the title, the icon and the counter badge
+    pub title_color: [f32; 4],
+    /// Bar along the left edge of the toast
+    pub accent_color: [f32; 4],
+    /// Width of the accent bar, 0.0 hides it
+    pub accent_width: f32,
+    /// Color of links in markup content
+    pub link_color: [f32; 4],
+    pub background: [f32; 4],
+    pub border_color: [f32; 4],
+    /// Width of the border, 0.0 hides it
+    pub border_size: f32,
+    pub rounding: f32,
+    pub padding: [f32; 2],
+}
+
+impl ToastStyle {
+    /// A toast matching imgui's dark style, `color` is used for the title and the accent bar.
+    pub fn dark(color: [f32; 4]) -> Self {
+        Self {
+            title_color: color,
+            accent_color: color,
+            accent_width: 3.0,
+            link_color: [0.45, 0.70, 1.0, 1.0],
+            background: [0.10, 0.10, 0.11, 0.94],
+            border_color: [0.43, 0.43, 0.50, 0.50],
+            border_size: 1.0,
+            rounding: 5.0,
+            padding: [10.0, 8.0],
+        }
+    }
+
+    /// A toast matching imgui's light style, `color` is used for the title and the accent bar.
+    pub fn light(color: [f32; 4]) -> Self {
+        Self {
+            link_color: [0.10, 0.35, 0.80, 1.0],
+            background: [0.96, 0.96, 0.96, 0.98],
+            border_color: [0.0, 0.0, 0.0, 0.30],
+            ..Self::dark(color)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CustomType {
+    name: String,
+    style: ToastStyle,
+    icon: Option<ToastIcon>,
+}
+
+/// Styles of the toast types, including custom ones.
+#[derive(Debug, Clone)]
+pub struct ToastTheme {
+    /// Styles of the types in [`ToastType::ALL`]
+    styles: [ToastStyle; ToastType::ALL.len()],
+    custom: Vec<CustomType>,
+}
+
+impl Default for ToastTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl ToastTheme {
+    /// Colors for imgui's dark style, the default.
+    pub fn dark() -> Self {
+        Self {
+            styles: [
+                ToastStyle {
+                    accent_width: 0.0,
+                    ..ToastStyle::dark([1.0, 1.0, 1.0, 1.0])
+                },
+                ToastStyle::dark([0.35, 0.85, 0.45, 1.0]),
+                ToastStyle::dark([1.0, 0.80, 0.25, 1.0]),
+                ToastStyle::dark([1.0, 0.40, 0.40, 1.0]),
+                ToastStyle::dark([0.35, 0.65, 1.0, 1.0]),
+            ],
+            custom: Vec::new(),
+        }
+    }
+
+    /// Colors for imgui's light style.
+    pub fn light() -> Self {
+        Self {
+            styles: [
+                ToastStyle {
+                    accent_width: 0.0,
+                    ..ToastStyle::light([0.0, 0.0, 0.0, 1.0])
+                },
+                ToastStyle::light([0.10, 0.55, 0.20, 1.0]),
+                ToastStyle::light([0.80, 0.50, 0.0, 1.0]),
+                ToastStyle::light([0.80, 0.10, 0.10, 1.0]),
+                ToastStyle::light([0.10, 0.40, 0.85, 1.0]),
+            ],
+            custom: Vec::new(),
+        }
+    }
+
+    /// Registers a type beyond [`ToastType`]'s, the returned type is used like the others.
+    /// `name` is the title of toasts without one.
+    pub fn add_type<S: Into<String>>(
+        &mut self,
+        name: S,
+        style: ToastStyle,
+        icon: Option<ToastIcon>,
+    ) -> ToastType {
+        self.custom.push(CustomType {
+            name: name.into(),
+            style,
+            icon,
+        });
+        ToastType::Custom(self.custom.len() - 1)
+    }
+
+    /// The style of `typ`, custom types that weren't registered look like [`ToastType::None`].
+    pub fn style(&self, typ: ToastType) -> &ToastStyle {
+        match typ {
+            ToastType::Custom(idx) => match self.custom.get(idx) {
+                Some(custom) => &custom.style,
+                None => &self.styles[ToastType::None.index()],
+            },
+            _ => &self.styles[typ.index()],
+        }
+    }
+
+    /// Panics for custom types that weren't registered.
+    pub fn style_mut(&mut self, typ: ToastType) -> &mut ToastStyle {
+        match typ {
+            ToastType::Custom(idx) => &mut self.custom[idx].style,
+            _ => &mut self.styles[typ.index()],
+        }
+    }
+
+    /// Title of toasts of `typ` without one.
+    pub(super) fn title(&self, typ: ToastType) -> Option<&str> {
+        match typ {
+            ToastType::Custom(idx) => self.custom.get(idx).map(|custom| custom.name.as_str()),
+            _ => typ.default_title(),
+        }
+    }
+
+    /// Icon of toasts of `typ` that didn't replace it.
+    pub(super) fn icon(&self, typ: ToastType) -> Option<ToastIcon> {
+        match typ {
+            ToastType::None => None,
+            ToastType::Success => Some(ToastIcon::Success),
+            ToastType::Warning => Some(ToastIcon::Warning),
+            ToastType::Error => Some(ToastIcon::Error),
+            ToastType::Info => Some(ToastIcon::Info),
+            ToastType::Custom(idx) => self.custom.get(idx).and_then(|custom| custom.icon),
+        }
+    }
+}
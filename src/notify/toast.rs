@@ -1,18 +1,64 @@
-use std::time::{Duration, Instant};
+use imgui::{TextureId, Ui};
+use std::time::{Duration, SystemTime};
 
-use super::{FADE_IN_OUT_TIME, OPACITY};
+use super::{
+    anim::{Animation, Easing, Frame},
+    markup::{self, Paragraph},
+    ToastIcon, FADE_IN_OUT_TIME,
+};
 
-#[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ToastType {
     None,
     Success,
     Warning,
     Error,
     Info,
+    /// A type registered with [`ToastTheme::add_type`](super::ToastTheme::add_type)
+    Custom(usize),
 }
 
-#[derive(Clone, Copy, PartialEq)]
+impl ToastType {
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::Success,
+        Self::Warning,
+        Self::Error,
+        Self::Info,
+    ];
+
+    pub(super) fn desc(&self) -> &'static str {
+        match self {
+            ToastType::None => "None",
+            ToastType::Success => "Success",
+            ToastType::Warning => "Warning",
+            ToastType::Error => "Error",
+            ToastType::Info => "Info",
+            ToastType::Custom(_) => "Other",
+        }
+    }
+
+    /// Index in `ALL`, custom types share the one past the end.
+    pub(super) fn index(&self) -> usize {
+        match self {
+            ToastType::None => 0,
+            ToastType::Success => 1,
+            ToastType::Warning => 2,
+            ToastType::Error => 3,
+            ToastType::Info => 4,
+            ToastType::Custom(_) => Self::ALL.len(),
+        }
+    }
+
+    pub(super) fn default_title(&self) -> Option<&'static str> {
+        match self {
+            ToastType::None | ToastType::Custom(_) => None,
+            _ => Some(self.desc()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub(super) enum ToastPhase {
     FadeIn,
     Wait,
@@ -20,62 +66,400 @@ pub(super) enum ToastPhase {
     Expired,
 }
 
+/// Identifies a pushed toast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ToastId(pub(super) u64);
+
+/// Something the user did with a toast, returned from [`Notifications::render`](super::Notifications::render).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToastEvent {
+    /// The toast was clicked outside of its buttons
+    Clicked(ToastId),
+    /// The close button was pressed
+    Closed(ToastId),
+    /// An action button was pressed, with the id it was added with
+    Action(ToastId, usize),
+    /// A link in markup content was clicked, with its url
+    Link(ToastId, String),
+}
+
+/// Content drawn after the text of a toast.
+pub(super) enum Block {
+    Image(TextureId, [f32; 2]),
+    Custom(Box<dyn Fn(&Ui) + Send>),
+}
+
+/// Progress of the job a toast is about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress {
+    /// A bar filled from 0.0 to 1.0
+    Determinate(f32),
+    /// A spinner
+    Indeterminate,
+}
+
 pub struct Toast {
+    id: ToastId,
     typ: ToastType,
+    /// The icon when replaced, `None` for the type's icon
+    icon: Option<Option<ToastIcon>>,
     title: Option<String>,
     content: Option<String>,
+    /// The content parsed from markup, drawn instead of `content`
+    paragraphs: Option<Vec<Paragraph>>,
+    blocks: Vec<Block>,
     dismiss_time: usize,
-    creation_time: Instant,
+    /// Time the toast was shown for, by the clock of the notifications
+    elapsed: Duration,
+    /// When the toast was made, for the history
+    time: SystemTime,
+    /// The dismiss timer is stopped while the toast is hovered
+    paused: bool,
+    /// Set once dismissed, hovering doesn't bring the toast back anymore
+    dismissed: bool,
+    closable: bool,
+    dismiss_on_click: bool,
+    actions: Vec<(String, usize)>,
+    /// Set while a job is running, the toast stays until completed
+    progress: Option<Progress>,
+    sticky: bool,
+    /// Toasts with the same key are shown as one
+    key: Option<String>,
+    /// Times the toast was pushed
+    count: usize,
+    enter: Animation,
+    exit: Animation,
+    easing: Easing,
+    fade_in_time: usize,
+    fade_out_time: usize,
+    /// Where the toast is drawn in the stack, moving towards where it belongs
+    stack_offset: Option<f32>,
+    /// Size of the window last frame, `None` until it was laid out with its contents
+    size: Option<[f32; 2]>,
+    /// Whether the toast was drawn before, the window it is drawn in is sized by the
+    /// contents of the frame before
+    drawn: bool,
 }
 
 impl Toast {
     pub fn new(typ: ToastType, duration: usize) -> Self {
         Self {
+            id: ToastId(0),
             typ,
+            icon: None,
             title: None,
             content: None,
+            paragraphs: None,
+            blocks: Vec::new(),
             dismiss_time: duration,
-            creation_time: Instant::now(),
+            elapsed: Duration::ZERO,
+            time: SystemTime::now(),
+            paused: false,
+            dismissed: false,
+            closable: false,
+            dismiss_on_click: false,
+            actions: Vec::new(),
+            progress: None,
+            sticky: false,
+            key: None,
+            count: 1,
+            enter: Animation::Fade,
+            exit: Animation::Fade,
+            easing: Easing::Linear,
+            fade_in_time: FADE_IN_OUT_TIME,
+            fade_out_time: FADE_IN_OUT_TIME,
+            stack_offset: None,
+            size: None,
+            drawn: false,
         }
     }
 
+    /// Replaces the icon of the toast's type.
+    pub fn icon(mut self, icon: ToastIcon) -> Self {
+        self.icon = Some(Some(icon));
+        self
+    }
+
+    pub fn no_icon(mut self) -> Self {
+        self.icon = Some(None);
+        self
+    }
+
     pub fn title<S: Into<String>>(mut self, title: S) -> Self {
         self.title = Some(title.into());
         self
     }
 
     pub fn content<S: Into<String>>(mut self, content: S) -> Self {
-        self.content = Some(content.into());
+        self.set_content(content);
+        self
+    }
+
+    /// Content with a tiny markup. Paragraphs are separated by blank lines, within them
+    /// `**bold**`, `{#rrggbb}colored{/}` (or `#rrggbbaa`) and `[links](url)` can be used, and `\`
+    /// escapes the next character. Clicked links are reported in a [`ToastEvent::Link`].
+    pub fn markup(mut self, markup: &str) -> Self {
+        self.set_markup(markup);
+        self
+    }
+
+    /// Adds an image after the content, tinted with the toast's opacity.
+    pub fn image(mut self, texture_id: TextureId, size: [f32; 2]) -> Self {
+        self.blocks.push(Block::Image(texture_id, size));
+        self
+    }
+
+    /// Adds arbitrary widgets after the content.
+    pub fn custom<F: Fn(&Ui) + Send + 'static>(mut self, draw: F) -> Self {
+        self.blocks.push(Block::Custom(Box::new(draw)));
+        self
+    }
+
+    /// Shows a close button.
+    pub fn closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    /// Dismisses the toast when it is clicked.
+    pub fn dismiss_on_click(mut self, dismiss_on_click: bool) -> Self {
+        self.dismiss_on_click = dismiss_on_click;
         self
     }
 
+    /// Adds a button, pressing it dismisses the toast and reports `id` in a
+    /// [`ToastEvent::Action`].
+    pub fn action<S: Into<String>>(mut self, label: S, id: usize) -> Self {
+        self.actions.push((label.into(), id));
+        self
+    }
+
+    /// Keeps the toast until it is dismissed, with a close button unless told otherwise.
+    pub fn sticky(mut self) -> Self {
+        self.sticky = true;
+        self.closable = true;
+        self
+    }
+
+    /// Groups this toast with a shown one of the same key, instead of showing both. Without a
+    /// key toasts are grouped by type, title and content when
+    /// [`NotificationsConfig::coalesce`](super::NotificationsConfig::coalesce) is set.
+    pub fn key<S: Into<String>>(mut self, key: S) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn animation(mut self, enter: Animation, exit: Animation) -> Self {
+        self.enter = enter;
+        self.exit = exit;
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// How long the enter and exit animations take in ms.
+    pub fn animation_time(mut self, enter: usize, exit: usize) -> Self {
+        self.fade_in_time = enter;
+        self.fade_out_time = exit;
+        self
+    }
+
+    /// Shows progress of a running job, the toast isn't dismissed until [`Toast::complete`] is
+    /// called.
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn set_title<S: Into<String>>(&mut self, title: S) {
+        self.title = Some(title.into());
+    }
+
+    pub fn set_content<S: Into<String>>(&mut self, content: S) {
+        self.content = Some(content.into());
+        self.paragraphs = None;
+    }
+
+    pub fn set_markup(&mut self, markup: &str) {
+        let (paragraphs, plain) = markup::parse(markup);
+        self.content = Some(plain);
+        self.paragraphs = Some(paragraphs);
+    }
+
+    pub fn set_type(&mut self, typ: ToastType) {
+        self.typ = typ;
+    }
+
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress = Some(progress);
+    }
+
+    /// Finishes the job, the progress is removed and the dismiss timer starts.
+    pub fn complete(&mut self, typ: ToastType) {
+        self.typ = typ;
+        if self.progress.take().is_some() {
+            self.set_elapsed_time(self.fade_in_time + 1);
+        }
+    }
+
+    pub(super) fn set_id(&mut self, id: ToastId) {
+        self.id = id;
+    }
+
+    pub(super) fn get_id(&self) -> ToastId {
+        self.id
+    }
+
+    pub(super) fn get_type(&self) -> ToastType {
+        self.typ
+    }
+
+    pub(super) fn get_time(&self) -> SystemTime {
+        self.time
+    }
+
+    pub(super) fn get_count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether `other` is a repeat of this toast.
+    pub(super) fn is_same(&self, other: &Toast, coalesce: bool) -> bool {
+        match (&self.key, &other.key) {
+            (Some(key), Some(other)) => key == other,
+            (None, None) => {
+                coalesce
+                    && self.typ == other.typ
+                    && self.title == other.title
+                    && self.content == other.content
+            }
+            _ => false,
+        }
+    }
+
+    /// Counts a repeat of this toast, taking its text and starting the dismiss timer over.
+    pub(super) fn repeat(&mut self, other: Toast) {
+        self.count += 1;
+        self.typ = other.typ;
+        self.title = other.title;
+        self.content = other.content;
+        self.paragraphs = other.paragraphs;
+        self.blocks = other.blocks;
+        self.dismissed = false;
+        if matches!(self.get_phase(), ToastPhase::Wait | ToastPhase::FadeOut) {
+            self.set_elapsed_time(self.fade_in_time + 1);
+        }
+    }
+
+    pub(super) fn get_progress(&self) -> Option<Progress> {
+        self.progress
+    }
+
+    pub(super) fn get_actions(&self) -> &[(String, usize)] {
+        &self.actions
+    }
+
+    pub(super) fn is_closable(&self) -> bool {
+        self.closable
+    }
+
+    pub(super) fn is_dismissed_on_click(&self) -> bool {
+        self.dismiss_on_click
+    }
+
+    /// Whether the toast has anything to click, toasts without let clicks through.
+    pub(super) fn is_interactive(&self) -> bool {
+        let has_links = self
+            .paragraphs
+            .iter()
+            .flatten()
+            .flatten()
+            .any(|span| span.link.is_some());
+        let has_widgets = self
+            .blocks
+            .iter()
+            .any(|block| matches!(block, Block::Custom(_)));
+        self.closable
+            || self.dismiss_on_click
+            || !self.actions.is_empty()
+            || has_links
+            || has_widgets
+    }
+
     fn get_elapsed_time(&self) -> Duration {
-        self.creation_time.elapsed()
+        self.elapsed
+    }
+
+    fn set_elapsed_time(&mut self, elapsed: usize) {
+        self.elapsed = Duration::from_millis(elapsed as u64);
+    }
+
+    /// Advances the timers by the time since the last frame.
+    pub(super) fn tick(&mut self, delta: Duration) {
+        if !self.paused {
+            self.elapsed = self.elapsed.saturating_add(delta);
+        }
+    }
+
+    /// Stops the dismiss timer while paused. A toast that started fading out is shown again,
+    /// unless it was dismissed.
+    pub(super) fn set_paused(&mut self, paused: bool) {
+        if !paused || self.dismissed {
+            self.paused = false;
+            return;
+        }
+        match self.get_phase() {
+            ToastPhase::Wait => self.paused = true,
+            ToastPhase::FadeOut => {
+                self.set_elapsed_time(self.fade_in_time + self.dismiss_time);
+                self.paused = true;
+            }
+            ToastPhase::FadeIn | ToastPhase::Expired => {}
+        }
+    }
+
+    /// Starts the timers over, for a toast that was waiting in the queue.
+    pub(super) fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.paused = false;
+    }
+
+    /// Starts fading out.
+    pub(super) fn dismiss(&mut self) {
+        // Taken before the holds are lifted, a sticky toast is long past its dismiss time
+        let phase = self.get_phase();
+        self.dismissed = true;
+        self.paused = false;
+        self.progress = None;
+        self.sticky = false;
+        if matches!(phase, ToastPhase::FadeIn | ToastPhase::Wait) {
+            self.set_elapsed_time(self.fade_in_time + self.dismiss_time + 1);
+        }
     }
 
     pub(super) fn get_phase(&self) -> ToastPhase {
         let elapsed = self.get_elapsed_time().as_millis() as usize;
 
-        if elapsed > (FADE_IN_OUT_TIME + self.dismiss_time + FADE_IN_OUT_TIME) {
+        if (self.sticky || self.progress.is_some()) && elapsed > self.fade_in_time {
+            return ToastPhase::Wait;
+        }
+
+        if elapsed > (self.fade_in_time + self.dismiss_time + self.fade_out_time) {
             ToastPhase::Expired
-        } else if elapsed > FADE_IN_OUT_TIME + self.dismiss_time {
+        } else if elapsed > self.fade_in_time + self.dismiss_time {
             ToastPhase::FadeOut
-        } else if elapsed > FADE_IN_OUT_TIME {
+        } else if elapsed > self.fade_in_time {
             ToastPhase::Wait
         } else {
             ToastPhase::FadeIn
         }
     }
 
-    pub(super) fn get_icon(&self) -> Option<()> {
-        // match self.typ {
-        //     ToastType::None => todo!(),
-        //     ToastType::Success => todo!(),
-        //     ToastType::Warning => todo!(),
-        //     ToastType::Error => todo!(),
-        //     ToastType::Info => todo!(),
-        // }
-        None
+    /// The icon, `default` being the icon of the toast's type.
+    pub(super) fn get_icon(&self, default: Option<ToastIcon>) -> Option<ToastIcon> {
+        self.icon.unwrap_or(default)
     }
 
     pub(super) fn get_title(&self) -> Option<&str> {
@@ -86,43 +470,198 @@ impl Toast {
         self.content.as_deref()
     }
 
-    pub(super) fn get_default_title(&self) -> Option<&str> {
-        match self.title {
-            Some(_) => self.title.as_deref(),
-            None => match self.typ {
-                ToastType::None => None,
-                ToastType::Success => Some("Success"),
-                ToastType::Warning => Some("Warning"),
-                ToastType::Error => Some("Error"),
-                ToastType::Info => Some("Info"),
-            },
-        }
+    pub(super) fn get_paragraphs(&self) -> Option<&[Paragraph]> {
+        self.paragraphs.as_deref()
     }
 
+    pub(super) fn get_blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// How far the toast is shown, 0.0 hidden and 1.0 fully shown.
     pub(super) fn get_fade_percent(&self) -> f32 {
         let phase = self.get_phase();
-        let elapsed = self.get_elapsed_time();
+        let elapsed = self.get_elapsed_time().as_millis() as f32;
 
         if phase == ToastPhase::FadeIn {
-            (elapsed.as_millis() as f32) / (FADE_IN_OUT_TIME as f32) * OPACITY
+            elapsed / self.fade_in_time.max(1) as f32
         } else if phase == ToastPhase::FadeOut {
-            (1.0 - (((elapsed.as_millis() as f32)
-                - (FADE_IN_OUT_TIME as f32)
-                - (self.dismiss_time as f32))
-                / (FADE_IN_OUT_TIME as f32)))
-                * OPACITY
+            1.0 - ((elapsed - self.fade_in_time as f32 - self.dismiss_time as f32)
+                / self.fade_out_time.max(1) as f32)
         } else {
-            1.0 * OPACITY
+            1.0
         }
     }
 
-    pub(super) fn get_color(&self) -> [f32; 4] {
-        match self.typ {
-            ToastType::None => [255.0, 255.0, 255.0, 255.0],
-            ToastType::Success => [0.0, 255.0, 0.0, 255.0],
-            ToastType::Warning => [255.0, 255.0, 0.0, 255.0],
-            ToastType::Error => [255.0, 0.0, 0.0, 255.0],
-            ToastType::Info => [0.0, 157.0, 255.0, 255.0],
+    /// The enter or exit animation at this point.
+    pub(super) fn get_frame(&self) -> Frame {
+        let anim = if self.get_phase() == ToastPhase::FadeOut {
+            self.exit
+        } else {
+            self.enter
+        };
+        anim.frame(self.easing.apply(self.get_fade_percent()))
+    }
+
+    /// Moves the toast towards `target` in the stack, smoothly unless `smooth` is off.
+    pub(super) fn move_to(&mut self, target: f32, delta_time: f32, smooth: bool) -> f32 {
+        let offset = match self.stack_offset {
+            Some(offset) if smooth => {
+                // Covers most of the distance in about a tenth of a second at any frame rate
+                offset + (target - offset) * (1.0 - (-delta_time * 20.0).exp())
+            }
+            _ => target,
+        };
+        self.stack_offset = Some(offset);
+        offset
+    }
+
+    /// Records the size of the window drawn this frame. The first one is dropped, the window was
+    /// sized by another toast's contents or none.
+    pub(super) fn set_size(&mut self, size: [f32; 2]) {
+        if self.drawn {
+            self.size = Some(size);
         }
+        self.drawn = true;
+    }
+
+    pub(super) fn get_size(&self) -> Option<[f32; 2]> {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// A toast shown for 1000ms between 100ms fades.
+    fn info_toast() -> Toast {
+        Toast::new(ToastType::Info, 1000).animation_time(100, 100)
+    }
+
+    #[test]
+    fn first_size_is_dropped() {
+        let mut toast = info_toast();
+        toast.set_size([10.0, 10.0]);
+        assert_eq!(toast.get_size(), None);
+        toast.set_size([20.0, 30.0]);
+        assert_eq!(toast.get_size(), Some([20.0, 30.0]));
+    }
+
+    #[test]
+    fn phases_follow_the_timers() {
+        let mut toast = info_toast();
+        assert_eq!(toast.get_phase(), ToastPhase::FadeIn);
+        toast.tick(ms(100));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeIn);
+        toast.tick(ms(1));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        toast.tick(ms(999));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        toast.tick(ms(1));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.tick(ms(99));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.tick(ms(1));
+        assert_eq!(toast.get_phase(), ToastPhase::Expired);
+    }
+
+    #[test]
+    fn fade_percent() {
+        let mut toast = info_toast();
+        toast.tick(ms(50));
+        assert_eq!(toast.get_fade_percent(), 0.5);
+        toast.tick(ms(500));
+        assert_eq!(toast.get_fade_percent(), 1.0);
+        toast.tick(ms(600));
+        assert_eq!(toast.get_fade_percent(), 0.5);
+    }
+
+    #[test]
+    fn pausing_holds_and_brings_back_fading_toasts() {
+        let mut toast = info_toast();
+        toast.set_paused(true);
+        toast.tick(ms(200));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+
+        toast.set_paused(true);
+        toast.tick(ms(10_000));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+
+        toast.set_paused(false);
+        toast.tick(ms(1000));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+
+        toast.set_paused(true);
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        assert_eq!(toast.get_fade_percent(), 1.0);
+    }
+
+    #[test]
+    fn dismissing_fades_out() {
+        let mut toast = info_toast();
+        toast.tick(ms(10));
+        toast.dismiss();
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.tick(ms(100));
+        assert_eq!(toast.get_phase(), ToastPhase::Expired);
+    }
+
+    #[test]
+    fn hovering_keeps_dismissed_toasts_fading() {
+        let mut toast = info_toast().closable(true);
+        toast.tick(ms(500));
+        toast.set_paused(true);
+        toast.dismiss();
+        toast.set_paused(true);
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.tick(ms(50));
+        toast.set_paused(true);
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.tick(ms(50));
+        assert_eq!(toast.get_phase(), ToastPhase::Expired);
+    }
+
+    #[test]
+    fn sticky_and_progress_wait_until_done() {
+        let mut toast = info_toast().sticky();
+        toast.tick(ms(60_000));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        toast.dismiss();
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+
+        let mut toast = info_toast().progress(Progress::Indeterminate);
+        toast.tick(ms(60_000));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        toast.complete(ToastType::Success);
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        toast.tick(ms(1001));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+    }
+
+    #[test]
+    fn interactive_toasts() {
+        assert!(!info_toast().content("text").is_interactive());
+        assert!(info_toast().closable(true).is_interactive());
+        assert!(info_toast().dismiss_on_click(true).is_interactive());
+        assert!(info_toast().action("Undo", 0).is_interactive());
+        assert!(info_toast()
+            .markup("see [docs](https://docs)")
+            .is_interactive());
+        assert!(!info_toast().markup("**bold**").is_interactive());
+    }
+
+    #[test]
+    fn repeats_restart_the_timer() {
+        let mut toast = info_toast();
+        toast.tick(ms(1150));
+        assert_eq!(toast.get_phase(), ToastPhase::FadeOut);
+        toast.repeat(Toast::new(ToastType::Info, 1000));
+        assert_eq!(toast.get_phase(), ToastPhase::Wait);
+        assert_eq!(toast.get_count(), 2);
     }
 }
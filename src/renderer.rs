@@ -245,6 +245,17 @@ impl RendererImpl {
         )
     }
 
+    pub fn insert_texture(&mut self, texture: Texture) -> imgui::TextureId {
+        let id = imgui::TextureId::new(self.next_texture_id);
+        self.next_texture_id += 1;
+        self.textures.insert(id, texture);
+        id
+    }
+
+    pub fn remove_texture(&mut self, id: imgui::TextureId) -> Option<Texture> {
+        self.textures.remove(&id)
+    }
+
     #[inline]
     pub fn change_swapchain_format(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
         let prev_srgb = self.output_format.describe().srgb;
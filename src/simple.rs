@@ -65,6 +65,15 @@ where
     pub fn run<F2>(self, mut draw: F2) -> !
     where
         F2: FnMut(&mut Ui, &mut T) + 'static,
+    {
+        self.run_with_gfx(move |ui, state, _| draw(ui, state))
+    }
+
+    /// Like [`Framework::run`], with access to the graphics context to upload textures
+    /// before the frame is rendered.
+    pub fn run_with_gfx<F2>(self, mut draw: F2) -> !
+    where
+        F2: FnMut(&mut Ui, &mut T, &mut GfxContext) + 'static,
     {
         let Self {
             event_loop,
@@ -94,7 +103,7 @@ where
                         .expect("Couldn't prepare imgui frame");
 
                     let ui = imgui.frame();
-                    draw(ui, &mut state);
+                    draw(ui, &mut state, &mut gfx);
 
                     imgui_platform.prepare_render(ui, &window);
                     gfx.redraw(imgui.render(), window.inner_size());